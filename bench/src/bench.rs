@@ -1,7 +1,7 @@
 use block_mesh::ndshape::{ConstShape, ConstShape3u32};
 use block_mesh::{
-    greedy_quads, visible_block_faces, GreedyQuadsBuffer, MergeVoxel, UnitQuadBuffer, Voxel,
-    VoxelVisibility, RIGHT_HANDED_Y_UP_CONFIG,
+    binary_greedy_quads, greedy_quads, visible_block_faces, GreedyQuadsBuffer, MergeVoxel,
+    UnitQuadBuffer, Voxel, VoxelVisibility, RIGHT_HANDED_Y_UP_CONFIG,
 };
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
@@ -80,6 +80,44 @@ fn bench_sphere_greedy(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_sphere_binary_greedy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bench_sphere_binary_greedy");
+    let mut samples = [EMPTY; SampleShape::SIZE as usize];
+    for i in 0u32..(SampleShape::SIZE) {
+        let p = into_domain(16, SampleShape::delinearize(i));
+        samples[i as usize] = sphere_voxel(p);
+    }
+
+    // Do a single run first to allocate the buffer to the right size.
+    let mut buffer = GreedyQuadsBuffer::new(samples.len());
+    binary_greedy_quads(
+        &samples,
+        &SampleShape {},
+        [0; 3],
+        [17; 3],
+        &RIGHT_HANDED_Y_UP_CONFIG.faces,
+        &mut buffer,
+    );
+
+    group.bench_with_input(
+        BenchmarkId::from_parameter(format!("quads={}", buffer.quads.num_quads())),
+        &(),
+        |b, _| {
+            b.iter(|| {
+                binary_greedy_quads(
+                    &samples,
+                    &SampleShape {},
+                    [0; 3],
+                    [17; 3],
+                    &RIGHT_HANDED_Y_UP_CONFIG.faces,
+                    &mut buffer,
+                )
+            });
+        },
+    );
+    group.finish();
+}
+
 fn bench_empty_space_simple(c: &mut Criterion) {
     let mut group = c.benchmark_group("bench_empty_space_simple");
     let samples = [EMPTY; SampleShape::SIZE as usize];
@@ -154,6 +192,7 @@ criterion_group!(
     benches,
     bench_sphere_simple,
     bench_sphere_greedy,
+    bench_sphere_binary_greedy,
     bench_empty_space_simple,
     bench_empty_space_greedy
 );
@@ -177,10 +216,15 @@ impl Voxel for BoolVoxel {
 
 impl MergeVoxel for BoolVoxel {
     type MergeValue = Self;
+    type MergeValueFacingNeighbour = Self;
 
     fn merge_value(&self) -> Self::MergeValue {
         *self
     }
+
+    fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour {
+        *self
+    }
 }
 
 fn sphere_voxel([x, y, z]: [f32; 3]) -> BoolVoxel {
@@ -6,8 +6,8 @@ use bevy::render::settings::RenderCreation;
 use block_mesh::ilattice::glam::Vec3A;
 use block_mesh::ndshape::{ConstShape, ConstShape3u32};
 use block_mesh::{
-    greedy_quads, visible_block_faces, GreedyQuadsBuffer, MergeVoxel, UnitQuadBuffer, Voxel,
-    VoxelVisibility, RIGHT_HANDED_Y_UP_CONFIG,
+    greedy_quads, visible_block_faces, GreedyQuadsBuffer, MergeVoxel, MeshBuffer, MeshBuilder,
+    UnitQuadBuffer, Voxel, VoxelVisibility, RIGHT_HANDED_Y_UP_CONFIG,
 };
 
 use bevy::{
@@ -104,38 +104,9 @@ fn generate_simple_mesh(
         &faces,
         &mut buffer,
     );
-    let num_indices = buffer.num_quads() * 6;
-    let num_vertices = buffer.num_quads() * 4;
-    let mut indices = Vec::with_capacity(num_indices);
-    let mut positions = Vec::with_capacity(num_vertices);
-    let mut normals = Vec::with_capacity(num_vertices);
-    for (group, face) in buffer.groups.into_iter().zip(faces.into_iter()) {
-        for quad in group.into_iter() {
-            indices.extend_from_slice(&face.quad_mesh_indices(positions.len() as u32));
-            positions.extend_from_slice(&face.quad_mesh_positions(&quad.into(), 1.0));
-            normals.extend_from_slice(&face.quad_mesh_normals());
-        }
-    }
+    let mesh = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG).build(&buffer);
 
-    let mut render_mesh = Mesh::new(
-        PrimitiveTopology::TriangleList,
-        RenderAssetUsages::RENDER_WORLD,
-    );
-    render_mesh.insert_attribute(
-        Mesh::ATTRIBUTE_POSITION,
-        VertexAttributeValues::Float32x3(positions),
-    );
-    render_mesh.insert_attribute(
-        Mesh::ATTRIBUTE_NORMAL,
-        VertexAttributeValues::Float32x3(normals),
-    );
-    render_mesh.insert_attribute(
-        Mesh::ATTRIBUTE_UV_0,
-        VertexAttributeValues::Float32x2(vec![[0.0; 2]; num_vertices]),
-    );
-    render_mesh.insert_indices(Indices::U32(indices.clone()));
-
-    meshes.add(render_mesh)
+    meshes.add(into_render_mesh(mesh))
 }
 
 fn generate_greedy_mesh(
@@ -161,38 +132,30 @@ fn generate_greedy_mesh(
         &faces,
         &mut buffer,
     );
-    let num_indices = buffer.quads.num_quads() * 6;
-    let num_vertices = buffer.quads.num_quads() * 4;
-    let mut indices = Vec::with_capacity(num_indices);
-    let mut positions = Vec::with_capacity(num_vertices);
-    let mut normals = Vec::with_capacity(num_vertices);
-    for (group, face) in buffer.quads.groups.into_iter().zip(faces.into_iter()) {
-        for quad in group.into_iter() {
-            indices.extend_from_slice(&face.quad_mesh_indices(positions.len() as u32));
-            positions.extend_from_slice(&face.quad_mesh_positions(&quad, 1.0));
-            normals.extend_from_slice(&face.quad_mesh_normals());
-        }
-    }
+    let mesh = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG).build(&buffer.quads);
 
+    meshes.add(into_render_mesh(mesh))
+}
+
+fn into_render_mesh(mesh: MeshBuffer) -> Mesh {
     let mut render_mesh = Mesh::new(
         PrimitiveTopology::TriangleList,
         RenderAssetUsages::RENDER_WORLD,
     );
     render_mesh.insert_attribute(
         Mesh::ATTRIBUTE_POSITION,
-        VertexAttributeValues::Float32x3(positions),
+        VertexAttributeValues::Float32x3(mesh.positions),
     );
     render_mesh.insert_attribute(
         Mesh::ATTRIBUTE_NORMAL,
-        VertexAttributeValues::Float32x3(normals),
+        VertexAttributeValues::Float32x3(mesh.normals),
     );
     render_mesh.insert_attribute(
         Mesh::ATTRIBUTE_UV_0,
-        VertexAttributeValues::Float32x2(vec![[0.0; 2]; num_vertices]),
+        VertexAttributeValues::Float32x2(mesh.tex_coords),
     );
-    render_mesh.insert_indices(Indices::U32(indices.clone()));
-
-    meshes.add(render_mesh)
+    render_mesh.insert_indices(Indices::U32(mesh.indices));
+    render_mesh
 }
 
 fn spawn_pbr(
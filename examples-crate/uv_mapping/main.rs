@@ -6,7 +6,8 @@ use bevy::render::render_resource::PrimitiveTopology;
 use bevy::render::texture::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor};
 use block_mesh::ndshape::{ConstShape, ConstShape3u32};
 use block_mesh::{
-    greedy_quads, GreedyQuadsBuffer, MergeVoxel, Voxel, VoxelVisibility, RIGHT_HANDED_Y_UP_CONFIG,
+    greedy_quads, GreedyQuadsBuffer, MergeVoxel, MeshBuilder, Voxel, VoxelVisibility,
+    RIGHT_HANDED_Y_UP_CONFIG,
 };
 
 #[derive(Default, Clone, Copy, Debug, Eq, Hash, PartialEq, States)]
@@ -119,26 +120,11 @@ fn setup(
         &faces,
         &mut buffer,
     );
-    let num_indices = buffer.quads.num_quads() * 6;
-    let num_vertices = buffer.quads.num_quads() * 4;
-    let mut indices = Vec::with_capacity(num_indices);
-    let mut positions = Vec::with_capacity(num_vertices);
-    let mut normals = Vec::with_capacity(num_vertices);
-    let mut tex_coords = Vec::with_capacity(num_vertices);
-    for (group, face) in buffer.quads.groups.into_iter().zip(faces.into_iter()) {
-        for quad in group.into_iter() {
-            indices.extend_from_slice(&face.quad_mesh_indices(positions.len() as u32));
-            positions.extend_from_slice(&face.quad_mesh_positions(&quad, 1.0));
-            normals.extend_from_slice(&face.quad_mesh_normals());
-            tex_coords.extend_from_slice(&face.tex_coords(
-                RIGHT_HANDED_Y_UP_CONFIG.u_flip_face,
-                true,
-                &quad,
-            ));
-        }
-    }
+    let mut mesh = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG)
+        .flip_v(true)
+        .build(&buffer.quads);
     // Center the mesh.
-    for p in &mut positions {
+    for p in &mut mesh.positions {
         *p = (Vec3::from(*p) - Vec3::splat(10.0)).into();
     }
 
@@ -147,16 +133,16 @@ fn setup(
         RenderAssetUsages::RENDER_WORLD,
     );
 
-    for uv in tex_coords.iter_mut() {
+    for uv in mesh.tex_coords.iter_mut() {
         for c in uv.iter_mut() {
             *c *= UV_SCALE;
         }
     }
 
-    render_mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
-    render_mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
-    render_mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, tex_coords);
-    render_mesh.insert_indices(Indices::U32(indices));
+    render_mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, mesh.positions);
+    render_mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, mesh.normals);
+    render_mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, mesh.tex_coords);
+    render_mesh.insert_indices(Indices::U32(mesh.indices));
 
     commands.spawn((
         PbrBundle {
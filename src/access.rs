@@ -0,0 +1,45 @@
+use ndshape::Shape;
+
+/// Read access to voxels by linear index, which is how the meshers step from a voxel to its neighbours.
+///
+/// Points are linearized by [`VoxelAccess::shape`], so a step along an axis always has the same stride. Besides plain
+/// arrays, this is implemented by [`ChunkNeighborhood`](crate::ChunkNeighborhood), which reads across chunk borders
+/// without copying them into a padded array.
+pub trait VoxelAccess {
+    type Voxel;
+    type Shape: Shape<3, Coord = u32>;
+
+    /// The shape that linearizes points into indices. A visited mask for these voxels has [`Shape::usize`] elements.
+    fn shape(&self) -> &Self::Shape;
+
+    /// Returns the voxel at `index`.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be the linearized point of a voxel in bounds.
+    unsafe fn get_unchecked(&self, index: u32) -> &Self::Voxel;
+}
+
+/// A voxel array with the shape that it's linearized by.
+pub(crate) struct ShapedSlice<'a, T, S> {
+    pub voxels: &'a [T],
+    pub shape: &'a S,
+}
+
+impl<'a, T, S> VoxelAccess for ShapedSlice<'a, T, S>
+where
+    S: Shape<3, Coord = u32>,
+{
+    type Voxel = T;
+    type Shape = S;
+
+    #[inline]
+    fn shape(&self) -> &S {
+        self.shape
+    }
+
+    #[inline]
+    unsafe fn get_unchecked(&self, index: u32) -> &T {
+        self.voxels.get_unchecked(index as usize)
+    }
+}
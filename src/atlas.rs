@@ -0,0 +1,150 @@
+use crate::{MeshBuilder, QuadGroups, SignedAxis};
+
+/// A texture atlas made of a uniform grid of tiles.
+///
+/// Tiles are numbered row by row, starting with the tile at UV `(0, 0)`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TextureAtlas {
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl TextureAtlas {
+    pub const fn new(columns: u32, rows: u32) -> Self {
+        Self { columns, rows }
+    }
+
+    /// Returns the UV rectangle `[min_u, min_v, max_u, max_v]` covered by `tile`.
+    pub fn tile_rect(&self, tile: u32) -> [f32; 4] {
+        debug_assert!(tile < self.columns * self.rows);
+        let size_u = 1.0 / self.columns as f32;
+        let size_v = 1.0 / self.rows as f32;
+        let min_u = (tile % self.columns) as f32 * size_u;
+        let min_v = (tile / self.columns) as f32 * size_v;
+        [min_u, min_v, min_u + size_u, min_v + size_v]
+    }
+}
+
+/// Per-vertex texture attributes for a [`TextureAtlas`], as returned by [`atlas_uvs`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AtlasUvs {
+    /// UV coordinates in the atlas. Each voxel face spans one tile, so a quad that is wider than one voxel runs past the
+    /// edge of its tile.
+    pub tex_coords: Vec<[f32; 2]>,
+    /// The `[min_u, min_v, max_u, max_v]` rectangle of the vertex's tile.
+    pub tile_rects: Vec<[f32; 4]>,
+}
+
+/// Calculates texture coordinates for `quads` in a texture atlas, in the same vertex order as [`MeshBuilder::build`].
+///
+/// `tile` is called with the face and minimum voxel of each quad to get the index of its tile in `atlas`. For quads from the
+/// greedy meshers, every voxel in a quad has the same merge value, so the minimum voxel stands in for all of them.
+///
+/// The tiles are oriented like [`OrientedBlockFace::tex_coords`](crate::OrientedBlockFace::tex_coords) with the
+/// `u_flip_face` and V flip of `builder`. To repeat the tile across a larger quad, a fragment shader wraps the interpolated
+/// `uv` into the interpolated `rect` with `rect.xy + fract((uv - rect.xy) / (rect.zw - rect.xy)) * (rect.zw - rect.xy)`.
+/// Quads from [`visible_block_faces`](crate::visible_block_faces) never leave their tiles, so they don't need this.
+pub fn atlas_uvs(
+    builder: &MeshBuilder,
+    atlas: &TextureAtlas,
+    quads: &impl QuadGroups,
+    mut tile: impl FnMut(SignedAxis, [u32; 3]) -> u32,
+) -> AtlasUvs {
+    let mut uvs = AtlasUvs {
+        tex_coords: Vec::with_capacity(4 * quads.num_quads()),
+        tile_rects: Vec::with_capacity(4 * quads.num_quads()),
+    };
+    quads.for_each_quad(|group_index, quad| {
+        let face = &builder.config.faces[group_index];
+        let rect @ [min_u, min_v, max_u, max_v] =
+            atlas.tile_rect(tile(face.normal_axis(), quad.minimum));
+        let tex_coords = face.tex_coords(builder.config.u_flip_face, builder.flip_v, &quad);
+        uvs.tex_coords.extend(
+            tex_coords.map(|[u, v]| [min_u + u * (max_u - min_u), min_v + v * (max_v - min_v)]),
+        );
+        uvs.tile_rects.extend([rect; 4]);
+    });
+    uvs
+}
+
+/// Calculates the array texture layer of every vertex of `quads`, in the same vertex order as [`MeshBuilder::build`].
+///
+/// `tile` is called with the face and minimum voxel of each quad to get its layer, like in [`atlas_uvs`]. The texture
+/// coordinates built by [`MeshBuilder`] already repeat once per voxel, so they can sample the layer with a repeating
+/// sampler.
+pub fn array_texture_layers(
+    builder: &MeshBuilder,
+    quads: &impl QuadGroups,
+    mut tile: impl FnMut(SignedAxis, [u32; 3]) -> u32,
+) -> Vec<u32> {
+    let mut layers = Vec::with_capacity(4 * quads.num_quads());
+    quads.for_each_quad(|group_index, quad| {
+        let face = &builder.config.faces[group_index];
+        layers.extend([tile(face.normal_axis(), quad.minimum); 4]);
+    });
+    layers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{QuadBuffer, UnorientedQuad, RIGHT_HANDED_Y_UP_CONFIG};
+
+    fn quads() -> QuadBuffer {
+        let mut quads = QuadBuffer::new();
+        for group in quads.groups.iter_mut() {
+            group.push(UnorientedQuad {
+                minimum: [1, 2, 3],
+                width: 2,
+                height: 3,
+            });
+        }
+        quads
+    }
+
+    // Grass on top, dirt on the bottom, and grass sides everywhere else.
+    fn grass_tile(face: SignedAxis, _: [u32; 3]) -> u32 {
+        match face {
+            SignedAxis::PosY => 0,
+            SignedAxis::NegY => 2,
+            _ => 1,
+        }
+    }
+
+    #[test]
+    fn tile_rects_cover_atlas() {
+        let atlas = TextureAtlas::new(4, 2);
+        assert_eq!(atlas.tile_rect(0), [0.0, 0.0, 0.25, 0.5]);
+        assert_eq!(atlas.tile_rect(5), [0.25, 0.5, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn atlas_uvs_follow_tex_coords() {
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG).flip_v(true);
+        let atlas = TextureAtlas::new(4, 4);
+        let quads = quads();
+        let mesh = builder.build(&quads);
+        let uvs = atlas_uvs(&builder, &atlas, &quads, grass_tile);
+
+        assert_eq!(uvs.tex_coords.len(), mesh.num_vertices());
+        assert_eq!(uvs.tile_rects.len(), mesh.num_vertices());
+        for (i, ([u, v], rect)) in uvs.tex_coords.iter().zip(uvs.tile_rects.iter()).enumerate() {
+            let face = RIGHT_HANDED_Y_UP_CONFIG.faces[i / 4].normal_axis();
+            assert_eq!(*rect, atlas.tile_rect(grass_tile(face, [1, 2, 3])));
+            // Mapping back into tile space gives the repeating texture coordinates.
+            let [min_u, min_v, max_u, max_v] = *rect;
+            let tile_uv = [(u - min_u) / (max_u - min_u), (v - min_v) / (max_v - min_v)];
+            assert_eq!(tile_uv, mesh.tex_coords[i]);
+        }
+    }
+
+    #[test]
+    fn array_texture_layers_per_vertex() {
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG);
+        let layers = array_texture_layers(&builder, &quads(), grass_tile);
+
+        // Faces are in the order -X, -Y, -Z, +X, +Y, +Z.
+        let expected: Vec<u32> = [1, 2, 1, 1, 0, 1].iter().flat_map(|&l| [l; 4]).collect();
+        assert_eq!(layers, expected);
+    }
+}
@@ -0,0 +1,436 @@
+//! Meshes the models in MagicaVoxel `.vox` files and writes them as OBJ or glTF binary files.
+//!
+//! Run `block-mesh --help` for usage.
+
+use block_mesh::ndshape::Shape;
+use block_mesh::{
+    greedy_quads, read_vox, visible_block_faces, write_glb, write_mtl, write_obj, Axis,
+    AxisTransform, GlbChunk, GreedyQuadsBuffer, MeshBuilder, MeshStats, OrientedBlockFace,
+    QuadCoordinateConfig, QuadGroups, UnitQuadBuffer, UnorientedQuad, VoxModel,
+    LEFT_HANDED_Y_UP_CONFIG, LEFT_HANDED_Z_UP_CONFIG, RIGHT_HANDED_Y_DOWN_CONFIG,
+    RIGHT_HANDED_Y_UP_CONFIG, RIGHT_HANDED_Z_UP_CONFIG,
+};
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: block-mesh [OPTIONS] <INPUT.vox>...
+
+Meshes every model in each input file and writes one output file per model, named after the input file. Files with more
+than one model get the model index appended to their name. Models are turned from the +Z up of MagicaVoxel to the up
+axis of the config.
+
+Options:
+  -a, --algorithm <ALGORITHM>  greedy or simple [default: greedy]
+  -c, --config <CONFIG>        The coordinate config: right-handed-y-up, left-handed-y-up, right-handed-z-up,
+                               left-handed-z-up, or right-handed-y-down [default: right-handed-y-up]. glTF is
+                               right-handed with +Y up, so glb only supports right-handed-y-up
+  -s, --voxel-size <SIZE>      The edge length of a voxel in output units [default: 1]
+  -f, --format <FORMAT>        obj or glb [default: glb]
+  -o, --out-dir <DIR>          The directory to write to [default: next to each input]
+  -h, --help                   Print this message
+";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Algorithm {
+    Greedy,
+    Simple,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Obj,
+    Glb,
+}
+
+struct Options {
+    algorithm: Algorithm,
+    config: &'static QuadCoordinateConfig,
+    /// Turns models from MagicaVoxel coordinates into those of `config`.
+    from_vox: AxisTransform,
+    voxel_size: f32,
+    format: Format,
+    out_dir: Option<PathBuf>,
+    inputs: Vec<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut status = ExitCode::SUCCESS;
+    for input in options.inputs.iter() {
+        if let Err(e) = mesh_file(&options, input) {
+            eprintln!("error: {}: {e}", input.display());
+            status = ExitCode::FAILURE;
+        }
+    }
+    status
+}
+
+/// Returns `None` if help was requested.
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        algorithm: Algorithm::Greedy,
+        config: &RIGHT_HANDED_Y_UP_CONFIG,
+        from_vox: AxisTransform::rotation(Axis::X, -1),
+        voxel_size: 1.0,
+        format: Format::Glb,
+        out_dir: None,
+        inputs: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{name} requires a value"))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-a" | "--algorithm" => {
+                options.algorithm = match value(&arg)?.as_str() {
+                    "greedy" => Algorithm::Greedy,
+                    "simple" => Algorithm::Simple,
+                    other => return Err(format!("unknown algorithm `{other}`")),
+                }
+            }
+            "-c" | "--config" => {
+                // MagicaVoxel is right-handed with +Z up. Left-handed configs also mirror the model so that it
+                // doesn't look mirrored in their coordinate system.
+                let y_up = AxisTransform::rotation(Axis::X, -1);
+                (options.config, options.from_vox) = match value(&arg)?.as_str() {
+                    "right-handed-y-up" => (&RIGHT_HANDED_Y_UP_CONFIG, y_up),
+                    "left-handed-y-up" => (
+                        &LEFT_HANDED_Y_UP_CONFIG,
+                        y_up.then(&AxisTransform::mirror(Axis::Z)),
+                    ),
+                    "right-handed-z-up" => (&RIGHT_HANDED_Z_UP_CONFIG, AxisTransform::IDENTITY),
+                    "left-handed-z-up" => {
+                        (&LEFT_HANDED_Z_UP_CONFIG, AxisTransform::mirror(Axis::Y))
+                    }
+                    "right-handed-y-down" => (
+                        &RIGHT_HANDED_Y_DOWN_CONFIG,
+                        AxisTransform::rotation(Axis::X, 1),
+                    ),
+                    other => return Err(format!("unknown config `{other}`")),
+                }
+            }
+            "-s" | "--voxel-size" => {
+                let size = value(&arg)?;
+                options.voxel_size = size
+                    .parse()
+                    .ok()
+                    .filter(|s: &f32| *s > 0.0)
+                    .ok_or_else(|| format!("invalid voxel size `{size}`"))?;
+            }
+            "-f" | "--format" => {
+                options.format = match value(&arg)?.as_str() {
+                    "obj" => Format::Obj,
+                    "glb" => Format::Glb,
+                    other => return Err(format!("unknown format `{other}`")),
+                }
+            }
+            "-o" | "--out-dir" => options.out_dir = Some(value(&arg)?.into()),
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ => options.inputs.push(arg.into()),
+        }
+    }
+
+    if options.inputs.is_empty() {
+        return Err("no input files".into());
+    }
+    if options.format == Format::Glb && *options.config != RIGHT_HANDED_Y_UP_CONFIG {
+        return Err(
+            "glTF is right-handed with +Y up, so glb can only be written with --config right-handed-y-up".into(),
+        );
+    }
+    Ok(Some(options))
+}
+
+fn mesh_file(options: &Options, input: &Path) -> Result<(), Box<dyn Error>> {
+    let vox = read_vox(&mut BufReader::new(File::open(input)?))?;
+    let faces = &options.config.faces;
+
+    let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+    let out_dir = match &options.out_dir {
+        Some(dir) => dir.as_path(),
+        None => input.parent().unwrap_or(Path::new("")),
+    };
+
+    for (model_index, model) in vox.models.iter().enumerate() {
+        let (shape, voxels) = orient(model, &options.from_vox).padded_voxels();
+        let max = shape.as_array().map(|d| d - 1);
+        // The palette index of the voxel that a quad was made from.
+        let palette_index =
+            |quad: &UnorientedQuad| voxels[shape.linearize(quad.minimum) as usize].0 as u32;
+
+        let name = if vox.models.len() > 1 {
+            format!("{stem}_{model_index}")
+        } else {
+            stem.to_string()
+        };
+
+        let [x, y, z] = model.size;
+        println!(
+            "{} model {model_index} ({x}x{y}x{z}, {} voxels):",
+            input.display(),
+            model.voxels.len()
+        );
+        let output = match options.algorithm {
+            Algorithm::Simple => {
+                let mut buffer = UnitQuadBuffer::new();
+                visible_block_faces(&voxels, &shape, [0; 3], max, faces, &mut buffer);
+                print_stats(faces, &buffer);
+                write_mesh(
+                    options,
+                    &buffer,
+                    out_dir,
+                    &name,
+                    &vox.palette,
+                    palette_index,
+                )?
+            }
+            Algorithm::Greedy => {
+                let mut buffer = GreedyQuadsBuffer::new(voxels.len());
+                greedy_quads(&voxels, &shape, [0; 3], max, faces, &mut buffer);
+                print_stats(faces, &buffer.quads);
+                write_mesh(
+                    options,
+                    &buffer.quads,
+                    out_dir,
+                    &name,
+                    &vox.palette,
+                    palette_index,
+                )?
+            }
+        };
+        println!("  wrote {}", output.display());
+    }
+    Ok(())
+}
+
+/// Applies `transform` to the size and voxel coordinates of `model`.
+fn orient(model: &VoxModel, transform: &AxisTransform) -> VoxModel {
+    VoxModel {
+        size: transform.transform_shape(model.size),
+        voxels: model
+            .voxels
+            .iter()
+            .map(|&(p, index)| {
+                // Models are at most 256 voxels across, so coordinates still fit in a byte.
+                let p = transform.transform_voxel(p.map(u32::from), model.size);
+                (p.map(|c| c as u8), index)
+            })
+            .collect(),
+    }
+}
+
+/// Writes `quads` to `out_dir` in the output format, returning the path of the mesh file.
+fn write_mesh(
+    options: &Options,
+    quads: &impl QuadGroups,
+    out_dir: &Path,
+    name: &str,
+    palette: &[[u8; 4]; 256],
+    palette_index: impl Fn(&UnorientedQuad) -> u32,
+) -> io::Result<PathBuf> {
+    let builder = MeshBuilder::new(options.config).voxel_size(options.voxel_size);
+    match options.format {
+        Format::Obj => {
+            let obj_path = out_dir.join(format!("{name}.obj"));
+            let mtl_name = format!("{name}.mtl");
+            let mut obj = BufWriter::new(File::create(&obj_path)?);
+            let materials = write_obj(&mut obj, &builder, quads, Some(&mtl_name), |_, quad| {
+                palette_index(quad)
+            })?;
+            let mut mtl = BufWriter::new(File::create(out_dir.join(&mtl_name))?);
+            write_mtl(&mut mtl, &materials, |id| {
+                let [r, g, b, _] = palette[id as usize];
+                [r, g, b].map(|c| c as f32 / 255.0)
+            })?;
+            Ok(obj_path)
+        }
+        Format::Glb => {
+            let glb_path = out_dir.join(format!("{name}.glb"));
+            let mut glb = BufWriter::new(File::create(&glb_path)?);
+            let chunks = [GlbChunk {
+                quads,
+                translation: [0.0; 3],
+            }];
+            write_glb(
+                &mut glb,
+                &builder,
+                &chunks,
+                |_, _, quad| palette_index(quad),
+                |id| {
+                    let [r, g, b, a] = palette[id as usize];
+                    [
+                        srgb_to_linear(r),
+                        srgb_to_linear(g),
+                        srgb_to_linear(b),
+                        a as f32 / 255.0,
+                    ]
+                },
+            )?;
+            Ok(glb_path)
+        }
+    }
+}
+
+/// Prints the number of quads in each face group, and how many fewer there are than from `visible_block_faces`.
+fn print_stats(faces: &[OrientedBlockFace; 6], quads: &impl QuadGroups) {
+    let stats = MeshStats::new(faces, quads);
+    let groups: Vec<String> = faces
+        .iter()
+        .zip(stats.quads_per_group)
+        .map(|(face, n)| format!("{} {n}", face_label(face)))
+        .collect();
+    println!(
+        "  {} quads, {} triangles ({})",
+        stats.num_quads(),
+        stats.num_triangles(),
+        groups.join(", ")
+    );
+    if stats.num_quads() > 0 {
+        let reduction = 100.0 * (1.0 - 1.0 / stats.compression_ratio());
+        println!(
+            "  {reduction:.1}% fewer than visible_block_faces ({} quads)",
+            stats.num_unit_quads()
+        );
+    }
+}
+
+/// A label like `+Y` for the normal of `face`.
+fn face_label(face: &OrientedBlockFace) -> String {
+    let normal = face.signed_normal();
+    let axis = ["X", "Y", "Z"][normal
+        .abs()
+        .to_array()
+        .iter()
+        .position(|&c| c != 0)
+        .unwrap()];
+    let sign = if normal.min_element() < 0 { '-' } else { '+' };
+    format!("{sign}{axis}")
+}
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend((content.len() as u32).to_le_bytes());
+        bytes.extend((children.len() as u32).to_le_bytes());
+        bytes.extend(content);
+        bytes.extend(children);
+        bytes
+    }
+
+    fn parse(args: &[&str]) -> Result<Option<Options>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    const CONFIGS: [&str; 5] = [
+        "right-handed-y-up",
+        "left-handed-y-up",
+        "right-handed-z-up",
+        "left-handed-z-up",
+        "right-handed-y-down",
+    ];
+
+    #[test]
+    fn rejects_configs_that_the_format_cant_represent() {
+        for config in CONFIGS {
+            assert!(parse(&["-f", "obj", "-c", config, "a.vox"]).is_ok());
+            let glb = parse(&["-f", "glb", "-c", config, "a.vox"]);
+            assert_eq!(glb.is_ok(), config == "right-handed-y-up", "{config}");
+        }
+    }
+
+    #[test]
+    fn converted_models_are_upright() {
+        // A column 3 voxels tall along the +Z up of MagicaVoxel, with a foot 1 voxel toward +Y.
+        let size: Vec<u8> = [1u32, 2, 3].iter().flat_map(|d| d.to_le_bytes()).collect();
+        let voxels = [[0, 0, 0, 1], [0, 0, 1, 1], [0, 0, 2, 1], [0, 1, 0, 2]];
+        let mut xyzi = (voxels.len() as u32).to_le_bytes().to_vec();
+        xyzi.extend(voxels.iter().flatten());
+        let mut children = chunk(b"SIZE", &size, &[]);
+        children.extend(chunk(b"XYZI", &xyzi, &[]));
+        let mut vox = b"VOX ".to_vec();
+        vox.extend(150u32.to_le_bytes());
+        vox.extend(chunk(b"MAIN", &[], &children));
+
+        let dir = std::env::temp_dir().join(format!("block-mesh-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("column.vox");
+        std::fs::write(&input, vox).unwrap();
+
+        // The up axis of each config, and the direction that the +Y of MagicaVoxel turns into.
+        let expected = [
+            ([0.0, 1.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+            ([0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+            ([0.0, -1.0, 0.0], [0.0, 0.0, 1.0]),
+        ];
+        for (config, (up, foot)) in CONFIGS.into_iter().zip(expected) {
+            let options = parse(&["-f", "obj", "-c", config, input.to_str().unwrap()])
+                .unwrap()
+                .unwrap();
+            mesh_file(&options, &input).unwrap();
+            let obj = std::fs::read_to_string(dir.join("column.obj")).unwrap();
+
+            let dot = |p: &[f32; 3], v: [f32; 3]| p[0] * v[0] + p[1] * v[1] + p[2] * v[2];
+            let positions: Vec<[f32; 3]> = obj
+                .lines()
+                .filter_map(|line| line.strip_prefix("v "))
+                .map(|line| {
+                    let c: Vec<f32> = line.split(' ').map(|c| c.parse().unwrap()).collect();
+                    [c[0], c[1], c[2]]
+                })
+                .collect();
+            let range = |v: [f32; 3]| {
+                let along = positions.iter().map(|p| dot(p, v));
+                let min = along.clone().fold(f32::INFINITY, f32::min);
+                (min, along.fold(f32::NEG_INFINITY, f32::max))
+            };
+            let (bottom, top) = range(up);
+            let (back, front) = range(foot);
+
+            // The column stands 3 voxels tall along the up axis, with the foot sticking out from its bottom.
+            assert_eq!((top - bottom, front - back), (3.0, 2.0), "{config}");
+            for p in positions.iter().filter(|p| dot(p, up) > bottom + 1.0) {
+                assert!(dot(p, foot) <= back + 1.0, "{config}");
+            }
+            assert!(
+                positions
+                    .iter()
+                    .any(|p| dot(p, up) == bottom + 1.0 && dot(p, foot) == front),
+                "{config}"
+            );
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,537 @@
+use crate::{
+    bounds::assert_in_bounds, face_is_visible, GreedyQuadsBuffer, MergeVoxel, OrientedBlockFace,
+    SignedAxis, UnorientedQuad, Voxel, VoxelVisibility,
+};
+
+use ilattice::glam::UVec3;
+use ndshape::Shape;
+
+/// The maximum size of `[min, max]` along any axis for [`binary_greedy_quads`]. With 1 voxel of padding on each side, this
+/// allows meshing 62^3 chunks.
+pub const BINARY_GREEDY_MAX_SIZE: u32 = u64::BITS;
+
+/// A bitmask-based implementation of [`greedy_quads`](crate::greedy_quads) for small chunks.
+///
+/// Occupancy of `[min, max]` is packed into `u64` rows along each axis, reading the visibility of each voxel only once.
+/// Visible faces are then found for a whole row at a time with bit operations, and only visible faces are compared by
+/// their merge values. The output is identical to [`greedy_quads`](crate::greedy_quads), so this can be used as a drop-in
+/// replacement.
+///
+/// `[min, max]` must be at most [`BINARY_GREEDY_MAX_SIZE`] voxels along every axis. The bitmasks are kept in `output`,
+/// so reusing it between calls avoids allocating them again.
+pub fn binary_greedy_quads<T, S>(
+    voxels: &[T],
+    voxels_shape: &S,
+    min: [u32; 3],
+    max: [u32; 3],
+    faces: &[OrientedBlockFace; 6],
+    output: &mut GreedyQuadsBuffer,
+) where
+    T: MergeVoxel,
+    S: Shape<3, Coord = u32>,
+{
+    assert_in_bounds(voxels, voxels_shape, min, max);
+
+    let min = UVec3::from(min);
+    let shape = UVec3::from(max) - min + UVec3::ONE;
+    assert!(
+        shape.max_element() <= BINARY_GREEDY_MAX_SIZE,
+        "[min, max] has shape {shape:?}; binary_greedy_quads supports at most {BINARY_GREEDY_MAX_SIZE} voxels per axis"
+    );
+
+    let GreedyQuadsBuffer {
+        quads,
+        ao,
+        occupancy,
+        ..
+    } = output;
+    quads.reset();
+    for group in ao.iter_mut() {
+        group.clear();
+    }
+
+    occupancy.fill(voxels, voxels_shape, min, shape.to_array());
+
+    for (group, face) in quads.groups.iter_mut().zip(faces.iter()) {
+        binary_greedy_quads_for_face(voxels, voxels_shape, min, occupancy, face, group);
+    }
+}
+
+/// The face visibility of every voxel in the extent, packed into rows of bits along each axis.
+///
+/// Usually every face of a voxel has the same visibility, so a single set of rows serves all face directions. Only when
+/// some voxel in the extent has a [`Voxel::get_face_visibility`] that differs between its faces are there separate rows
+/// for each face direction.
+///
+/// This lives in [`GreedyQuadsBuffer`] so that its allocations are reused between calls.
+#[derive(Default)]
+pub(crate) struct OccupancyRows {
+    shape: [u32; 3],
+    rows_per_axis: usize,
+    // Indexed by [axis][row] when `per_face` is false, and [face][axis][row] otherwise.
+    opaque: Vec<u64>,
+    translucent: Vec<u64>,
+    // Only when `per_face` is true, the voxels that aren't `Empty` as a whole, indexed by [axis][row]. Other voxels can
+    // still hide the faces of their neighbours, but never get faces of their own.
+    meshed: Vec<u64>,
+    per_face: bool,
+}
+
+impl OccupancyRows {
+    /// Packs the visibility of the voxels in the extent of `shape` at `min`, replacing any previous contents.
+    fn fill<T, S>(&mut self, voxels: &[T], voxels_shape: &S, min: UVec3, shape: [u32; 3])
+    where
+        T: Voxel,
+        S: Shape<3, Coord = u32>,
+    {
+        let [x_size, y_size, z_size] = shape.map(|s| s as usize);
+        self.shape = shape;
+        self.rows_per_axis = (y_size * z_size).max(x_size * z_size).max(x_size * y_size);
+        // Try a single set of rows first. The default `get_face_visibility` inlines to `get_visibility`, so this check
+        // costs nothing for voxel types that don't override it.
+        self.clear(false);
+        let uniform = self.for_each_voxel(voxels, voxels_shape, min, |voxel, rows, p| {
+            let visibility = voxel.get_visibility();
+            if SignedAxis::ALL
+                .into_iter()
+                .any(|face| voxel.get_face_visibility(face) != visibility)
+            {
+                return false;
+            }
+            let row_indices = [0, 1, 2].map(|axis| rows.row_index(axis, p));
+            let bits = match visibility {
+                VoxelVisibility::Empty => return true,
+                VoxelVisibility::Translucent => &mut rows.translucent,
+                VoxelVisibility::Opaque => &mut rows.opaque,
+            };
+            for axis in 0..3 {
+                bits[row_indices[axis]] |= 1 << p[axis];
+            }
+            true
+        });
+        if uniform {
+            return;
+        }
+
+        self.clear(true);
+        self.for_each_voxel(voxels, voxels_shape, min, |voxel, rows, p| {
+            let row_indices = [0, 1, 2].map(|axis| rows.row_index(axis, p));
+            if voxel.get_visibility() != VoxelVisibility::Empty {
+                for axis in 0..3 {
+                    rows.meshed[row_indices[axis]] |= 1 << p[axis];
+                }
+            }
+            for face in SignedAxis::ALL {
+                let bits = match voxel.get_face_visibility(face) {
+                    VoxelVisibility::Empty => continue,
+                    VoxelVisibility::Translucent => &mut rows.translucent,
+                    VoxelVisibility::Opaque => &mut rows.opaque,
+                };
+                let face_offset = 3 * face as usize * rows.rows_per_axis;
+                for axis in 0..3 {
+                    bits[face_offset + row_indices[axis]] |= 1 << p[axis];
+                }
+            }
+            true
+        });
+    }
+
+    /// Zeroes the rows for the current shape, with a set of rows for each face direction if `per_face` is true.
+    fn clear(&mut self, per_face: bool) {
+        let len = if per_face { 18 } else { 3 } * self.rows_per_axis;
+        self.per_face = per_face;
+        for bits in [&mut self.opaque, &mut self.translucent] {
+            bits.clear();
+            bits.resize(len, 0);
+        }
+        self.meshed.clear();
+        if per_face {
+            self.meshed.resize(3 * self.rows_per_axis, 0);
+        }
+    }
+
+    /// Calls `set_bits` on every voxel in the extent with its local point, stopping early if it returns false. Returns
+    /// whether every voxel was visited.
+    fn for_each_voxel<T, S>(
+        &mut self,
+        voxels: &[T],
+        voxels_shape: &S,
+        min: UVec3,
+        mut set_bits: impl FnMut(&T, &mut Self, [u32; 3]) -> bool,
+    ) -> bool
+    where
+        S: Shape<3, Coord = u32>,
+    {
+        let shape = self.shape;
+        for z in 0..shape[2] {
+            for y in 0..shape[1] {
+                let row_index = voxels_shape.linearize((min + UVec3::new(0, y, z)).to_array());
+                for x in 0..shape[0] {
+                    let voxel = unsafe { voxels.get_unchecked((row_index + x) as usize) };
+                    if !set_bits(voxel, self, [x, y, z]) {
+                        return false;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// The index of the row along `axis` that contains the local point `p`.
+    #[inline]
+    fn row_index(&self, axis: usize, p: [u32; 3]) -> usize {
+        let (b, c) = match axis {
+            0 => (1, 2),
+            1 => (0, 2),
+            _ => (0, 1),
+        };
+        axis * self.rows_per_axis + (p[b] + p[c] * self.shape[b]) as usize
+    }
+
+    /// Returns the `(opaque, translucent)` rows of `face` visibilities along `axis` that contain the local point `p`.
+    #[inline]
+    fn get(&self, face: SignedAxis, axis: usize, p: [u32; 3]) -> (u64, u64) {
+        let mut i = self.row_index(axis, p);
+        if self.per_face {
+            i += 3 * face as usize * self.rows_per_axis;
+        }
+        (self.opaque[i], self.translucent[i])
+    }
+
+    /// Returns the row of voxels along `axis` that contains the local point `p` and can have faces of their own.
+    #[inline]
+    fn meshed(&self, axis: usize, p: [u32; 3]) -> u64 {
+        if self.per_face {
+            self.meshed[self.row_index(axis, p)]
+        } else {
+            // Voxels that are `Empty` as a whole have no visibility bits at all.
+            !0
+        }
+    }
+}
+
+fn binary_greedy_quads_for_face<T, S>(
+    voxels: &[T],
+    voxels_shape: &S,
+    min: UVec3,
+    rows: &OccupancyRows,
+    face: &OrientedBlockFace,
+    quads: &mut Vec<UnorientedQuad>,
+) where
+    T: MergeVoxel,
+    S: Shape<3, Coord = u32>,
+{
+    let [n_axis, u_axis, v_axis] = face.permutation.axes();
+    let i_n = n_axis.index();
+    let i_u = u_axis.index();
+    let i_v = v_axis.index();
+
+    let normal = face.normal_axis();
+    let shape = rows.shape;
+    // Only voxels on the interior of the extent are meshed.
+    let interior_u_mask = ((1u64 << (shape[i_u] - 1)) - 1) & !1;
+    let v_range = 1..shape[i_v].saturating_sub(1);
+
+    let n_stride = voxels_shape.linearize(face.n.to_array());
+    let u_stride = voxels_shape.linearize(face.u.to_array());
+    let v_stride = voxels_shape.linearize(face.v.to_array());
+    let visibility_offset = if face.n_sign > 0 {
+        n_stride
+    } else {
+        0u32.wrapping_sub(n_stride)
+    };
+    let min_index = voxels_shape.linearize(min.to_array());
+
+    // The visible faces of each row along U in the current slice, indexed by V.
+    let mut face_rows = [0u64; BINARY_GREEDY_MAX_SIZE as usize];
+
+    for slice in 1..shape[i_n].saturating_sub(1) {
+        let neighbour_slice = slice.wrapping_add_signed(face.n_sign);
+
+        for v in v_range.clone() {
+            let mut p = [0; 3];
+            p[i_n] = slice;
+            p[i_v] = v;
+            let (opaque, translucent) = rows.get(normal, i_u, p);
+            let meshed = rows.meshed(i_u, p);
+            let (opaque, translucent) = (opaque & meshed, translucent & meshed);
+            p[i_n] = neighbour_slice;
+            let (neighbour_opaque, neighbour_translucent) = rows.get(normal.opposite(), i_u, p);
+
+            let mut visible = (opaque & !neighbour_opaque)
+                | (translucent & !neighbour_opaque & !neighbour_translucent);
+
+            // Faces between two translucent voxels depend on their translucency groups.
+            let mut both_translucent = translucent & neighbour_translucent & interior_u_mask;
+            while both_translucent != 0 {
+                let u = both_translucent.trailing_zeros();
+                both_translucent &= both_translucent - 1;
+
+                let index = min_index + slice * n_stride + u * u_stride + v * v_stride;
+                let voxel = unsafe { voxels.get_unchecked(index as usize) };
+                let neighbour =
+                    unsafe { voxels.get_unchecked(index.wrapping_add(visibility_offset) as usize) };
+                if face_is_visible(voxel, neighbour, normal) {
+                    visible |= 1 << u;
+                }
+            }
+
+            face_rows[v as usize] = visible & interior_u_mask;
+        }
+
+        let slice_index = min_index + slice * n_stride;
+        let mut emit_quad = |face_rows: &mut [u64], u: u32, v: u32| {
+            let quad_min_index = slice_index + u * u_stride + v * v_stride;
+            let (width, height) = unsafe {
+                find_quad(
+                    voxels,
+                    face_rows,
+                    quad_min_index,
+                    u,
+                    v,
+                    v_range.end,
+                    u_stride,
+                    v_stride,
+                    visibility_offset,
+                    normal,
+                )
+            };
+
+            // Mark the quad as visited.
+            let quad_row_mask = row_mask(u, width);
+            for row in &mut face_rows[v as usize..(v + height) as usize] {
+                *row &= !quad_row_mask;
+            }
+
+            let mut minimum = min.to_array();
+            minimum[i_n] += slice;
+            minimum[i_u] += u;
+            minimum[i_v] += v;
+            quads.push(UnorientedQuad {
+                minimum,
+                width,
+                height,
+            });
+        };
+
+        // Visit quad minimums in the same order as `greedy_quads`, which iterates over the slice with X varying fastest.
+        if i_u < i_v {
+            for v in v_range.clone() {
+                while face_rows[v as usize] != 0 {
+                    let u = face_rows[v as usize].trailing_zeros();
+                    emit_quad(&mut face_rows, u, v);
+                }
+            }
+        } else {
+            for u in 1..shape[i_u].saturating_sub(1) {
+                for v in v_range.clone() {
+                    if face_rows[v as usize] & (1 << u) != 0 {
+                        emit_quad(&mut face_rows, u, v);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Finds the biggest quad starting at `(u, v)` where all faces are visible and have the same merge values, preferring width
+/// over height just like [`VoxelMerger`](crate::VoxelMerger).
+#[allow(clippy::too_many_arguments)]
+unsafe fn find_quad<T>(
+    voxels: &[T],
+    face_rows: &[u64],
+    min_index: u32,
+    u: u32,
+    v: u32,
+    v_end: u32,
+    u_stride: u32,
+    v_stride: u32,
+    visibility_offset: u32,
+    normal: SignedAxis,
+) -> (u32, u32)
+where
+    T: MergeVoxel,
+{
+    let quad_value = voxels
+        .get_unchecked(min_index as usize)
+        .merge_value_for_face(normal);
+    let quad_neighbour_value = voxels
+        .get_unchecked(min_index.wrapping_add(visibility_offset) as usize)
+        .merge_value_facing_neighbour();
+    let merges = |index: u32| {
+        voxels
+            .get_unchecked(index as usize)
+            .merge_value_for_face(normal)
+            == quad_value
+            && voxels
+                .get_unchecked(index.wrapping_add(visibility_offset) as usize)
+                .merge_value_facing_neighbour()
+                == quad_neighbour_value
+    };
+
+    // Start by finding the widest quad in the U direction.
+    let visible_run = (face_rows[v as usize] >> u).trailing_ones();
+    let mut width = 1;
+    while width < visible_run && merges(min_index + width * u_stride) {
+        width += 1;
+    }
+
+    // Now see how tall we can make the quad in the V direction without changing the width.
+    let quad_row_mask = row_mask(u, width);
+    let mut height = 1;
+    let mut row_start_index = min_index + v_stride;
+    while v + height < v_end
+        && face_rows[(v + height) as usize] & quad_row_mask == quad_row_mask
+        && (0..width).all(|i| merges(row_start_index + i * u_stride))
+    {
+        height += 1;
+        row_start_index += v_stride;
+    }
+
+    (width, height)
+}
+
+/// A mask of `width` bits starting at bit `u`.
+#[inline]
+fn row_mask(u: u32, width: u32) -> u64 {
+    (u64::MAX >> (u64::BITS - width)) << u
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{Material, XorShift};
+    use crate::{greedy_quads, RIGHT_HANDED_Y_UP_CONFIG};
+    use ndshape::{ConstShape, ConstShape3u32};
+
+    #[test]
+    fn matches_greedy_quads() {
+        let samples = noise_samples();
+        for (min, max) in [([0; 3], [33; 3]), ([2, 5, 1], [30, 20, 12])] {
+            let mut expected = GreedyQuadsBuffer::new(samples.len());
+            greedy_quads(
+                &samples,
+                &SampleShape {},
+                min,
+                max,
+                &RIGHT_HANDED_Y_UP_CONFIG.faces,
+                &mut expected,
+            );
+            let mut buffer = GreedyQuadsBuffer::new(samples.len());
+            binary_greedy_quads(
+                &samples,
+                &SampleShape {},
+                min,
+                max,
+                &RIGHT_HANDED_Y_UP_CONFIG.faces,
+                &mut buffer,
+            );
+
+            assert!(expected.quads.num_quads() > 0);
+            assert_eq!(buffer.quads.groups, expected.quads.groups);
+        }
+    }
+
+    #[test]
+    fn matches_greedy_quads_on_max_size_chunk() {
+        type ChunkShape = ConstShape3u32<64, 64, 64>;
+        let samples: Vec<_> = (0..ChunkShape::SIZE)
+            .map(|i| {
+                let [x, y, z] = ChunkShape {}.delinearize(i).map(|c| c as i32 - 32);
+                match x * x + y * y + z * z {
+                    d if d < 20 * 20 => Material::Water,
+                    d if d < 29 * 29 => Material::Stone,
+                    d if d < 31 * 31 => Material::Glass,
+                    _ => Material::Air,
+                }
+            })
+            .collect();
+
+        let mut expected = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads(
+            &samples,
+            &ChunkShape {},
+            [0; 3],
+            [63; 3],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut expected,
+        );
+        let mut buffer = GreedyQuadsBuffer::new(samples.len());
+        binary_greedy_quads(
+            &samples,
+            &ChunkShape {},
+            [0; 3],
+            [63; 3],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+
+        assert!(expected.quads.num_quads() > 0);
+        assert_eq!(buffer.quads.groups, expected.quads.groups);
+    }
+
+    #[test]
+    fn finds_face_visibility_late_in_extent() {
+        // Every voxel before the slab has the same visibility on all of its faces.
+        type RowShape = ConstShape3u32<5, 3, 3>;
+        let mut samples = [Material::Air; RowShape::SIZE as usize];
+        samples[RowShape {}.linearize([1, 1, 1]) as usize] = Material::Stone;
+        samples[RowShape {}.linearize([2, 1, 1]) as usize] = Material::Stone;
+        samples[RowShape {}.linearize([3, 1, 1]) as usize] = Material::Slab;
+
+        let mut expected = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads(
+            &samples,
+            &RowShape {},
+            [0; 3],
+            [4, 2, 2],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut expected,
+        );
+        let mut buffer = GreedyQuadsBuffer::new(samples.len());
+        binary_greedy_quads(
+            &samples,
+            &RowShape {},
+            [0; 3],
+            [4, 2, 2],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+
+        assert_eq!(buffer.quads.groups, expected.quads.groups);
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_with_extent_larger_than_max_size() {
+        type LargeShape = ConstShape3u32<66, 3, 3>;
+        let samples = [Material::Air; LargeShape::SIZE as usize];
+        let mut buffer = GreedyQuadsBuffer::new(samples.len());
+        binary_greedy_quads(
+            &samples,
+            &LargeShape {},
+            [0; 3],
+            [65, 2, 2],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+    }
+
+    type SampleShape = ConstShape3u32<34, 34, 34>;
+
+    /// Pseudo-random voxels with a mix of materials and visibilities.
+    fn noise_samples() -> [Material; SampleShape::SIZE as usize] {
+        let mut samples = [Material::Air; SampleShape::SIZE as usize];
+        let mut rng = XorShift(0x2545_f491);
+        for sample in samples.iter_mut() {
+            *sample = match rng.next() % 9 {
+                0..=2 => Material::Air,
+                3..=5 => Material::Stone,
+                6 => Material::Glass,
+                7 => Material::Water,
+                _ => Material::Slab,
+            };
+        }
+        samples
+    }
+}
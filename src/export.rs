@@ -0,0 +1,14 @@
+mod glb;
+mod obj;
+mod ply;
+mod stl;
+
+pub use glb::*;
+pub use obj::*;
+pub use ply::*;
+pub use stl::*;
+
+/// The name of material `id` in the files written by [`write_obj`], [`write_mtl`], and [`write_glb`].
+pub fn material_name(id: u32) -> String {
+    format!("material_{id}")
+}
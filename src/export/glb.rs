@@ -0,0 +1,447 @@
+use super::material_name;
+use crate::{
+    MeshBuffer, MeshBuilder, QuadBuffer, QuadGroups, UnorientedQuad, RIGHT_HANDED_Y_UP_CONFIG,
+};
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_VERSION: u32 = 2;
+const CHUNK_TYPE_JSON: u32 = 0x4E4F_534A;
+const CHUNK_TYPE_BIN: u32 = 0x004E_4942;
+
+const COMPONENT_TYPE_FLOAT: u32 = 5126;
+const COMPONENT_TYPE_UNSIGNED_INT: u32 = 5125;
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const MODE_TRIANGLES: u32 = 4;
+
+/// One chunk of quads to write with [`write_glb`], which becomes a node with its own mesh.
+pub struct GlbChunk<'a, Q> {
+    pub quads: &'a Q,
+    /// The translation of the chunk's node, usually the world position of the chunk's minimum voxel.
+    pub translation: [f32; 3],
+}
+
+/// Writes `chunks` as a self-contained glTF 2.0 binary (`.glb`) file, using the coordinate system, voxel size, and V flip of
+/// `builder`.
+///
+/// Each chunk becomes a node in the default scene, with a mesh that has one primitive per material. `material` is called with
+/// the chunk index, group index, and each quad to get its material ID. Every distinct material ID gets a glTF material named
+/// by [`material_name`], with the base color returned by `base_color` (linear RGBA in `[0, 1]`).
+///
+/// Chunks without any quads become nodes without a mesh.
+///
+/// glTF is always right-handed with +Y up, so `builder` must use [`RIGHT_HANDED_Y_UP_CONFIG`]. Other configurations are
+/// rejected with [`io::ErrorKind::InvalidInput`] before anything is written. They aren't converted, because a
+/// configuration doesn't determine its coordinate system:
+/// [`LEFT_HANDED_Y_UP_CONFIG`](crate::LEFT_HANDED_Y_UP_CONFIG) and
+/// [`RIGHT_HANDED_Y_DOWN_CONFIG`](crate::RIGHT_HANDED_Y_DOWN_CONFIG) are the same.
+pub fn write_glb<Q>(
+    writer: &mut impl Write,
+    builder: &MeshBuilder,
+    chunks: &[GlbChunk<Q>],
+    mut material: impl FnMut(usize, usize, &UnorientedQuad) -> u32,
+    mut base_color: impl FnMut(u32) -> [f32; 4],
+) -> io::Result<()>
+where
+    Q: QuadGroups,
+{
+    if builder.config != RIGHT_HANDED_Y_UP_CONFIG {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "glTF requires RIGHT_HANDED_Y_UP_CONFIG",
+        ));
+    }
+
+    let mut chunk_materials = Vec::with_capacity(chunks.len());
+    let mut material_ids = BTreeMap::new();
+    for (chunk_index, chunk) in chunks.iter().enumerate() {
+        let mut quads_by_material = BTreeMap::<u32, QuadBuffer>::new();
+        chunk.quads.for_each_quad(|group_index, quad| {
+            let id = material(chunk_index, group_index, &quad);
+            quads_by_material.entry(id).or_default().groups[group_index].push(quad);
+        });
+        for &id in quads_by_material.keys() {
+            let next_index = material_ids.len();
+            material_ids.entry(id).or_insert(next_index);
+        }
+        chunk_materials.push(quads_by_material);
+    }
+
+    let mut json = GltfJson::default();
+    let mut bin = Vec::new();
+    let mut mesh = MeshBuffer::new();
+    for (chunk, quads_by_material) in chunks.iter().zip(chunk_materials.iter()) {
+        let [x, y, z] = chunk.translation;
+        if quads_by_material.is_empty() {
+            json.nodes
+                .push(format!(r#"{{"translation":[{x},{y},{z}]}}"#));
+            continue;
+        }
+
+        let mut primitives = Vec::new();
+        for (id, quads) in quads_by_material.iter() {
+            builder.build_into(quads, &mut mesh);
+            let (position_min, position_max) = bounds(&mesh.positions);
+            let position = json.push_accessor(
+                &mut bin,
+                &f32_bytes(&mesh.positions),
+                TARGET_ARRAY_BUFFER,
+                COMPONENT_TYPE_FLOAT,
+                mesh.positions.len(),
+                "VEC3",
+                Some((&position_min, &position_max)),
+            );
+            let normal = json.push_accessor(
+                &mut bin,
+                &f32_bytes(&mesh.normals),
+                TARGET_ARRAY_BUFFER,
+                COMPONENT_TYPE_FLOAT,
+                mesh.normals.len(),
+                "VEC3",
+                None,
+            );
+            let tex_coord = json.push_accessor(
+                &mut bin,
+                &f32_bytes(&mesh.tex_coords),
+                TARGET_ARRAY_BUFFER,
+                COMPONENT_TYPE_FLOAT,
+                mesh.tex_coords.len(),
+                "VEC2",
+                None,
+            );
+            let indices = json.push_accessor(
+                &mut bin,
+                &u32_bytes(&mesh.indices),
+                TARGET_ELEMENT_ARRAY_BUFFER,
+                COMPONENT_TYPE_UNSIGNED_INT,
+                mesh.indices.len(),
+                "SCALAR",
+                None,
+            );
+            primitives.push(format!(
+                r#"{{"attributes":{{"POSITION":{position},"NORMAL":{normal},"TEXCOORD_0":{tex_coord}}},"indices":{indices},"material":{},"mode":{MODE_TRIANGLES}}}"#,
+                material_ids[id]
+            ));
+        }
+
+        json.nodes.push(format!(
+            r#"{{"mesh":{},"translation":[{x},{y},{z}]}}"#,
+            json.meshes.len()
+        ));
+        json.meshes
+            .push(format!(r#"{{"primitives":[{}]}}"#, primitives.join(",")));
+    }
+
+    json.materials = vec![String::new(); material_ids.len()];
+    for (&id, &index) in material_ids.iter() {
+        let [r, g, b, a] = base_color(id);
+        json.materials[index] = format!(
+            r#"{{"name":"{}","pbrMetallicRoughness":{{"baseColorFactor":[{r},{g},{b},{a}],"metallicFactor":0,"roughnessFactor":1}}}}"#,
+            material_name(id)
+        );
+    }
+
+    let json = json.finish(bin.len());
+    write_container(writer, json.as_bytes(), &bin)
+}
+
+/// The top-level arrays of the glTF JSON, with each element already serialized.
+#[derive(Default)]
+struct GltfJson {
+    nodes: Vec<String>,
+    meshes: Vec<String>,
+    materials: Vec<String>,
+    accessors: Vec<String>,
+    buffer_views: Vec<String>,
+}
+
+impl GltfJson {
+    /// Appends `data` to `bin` in a new buffer view, and returns the index of a new accessor for it.
+    #[allow(clippy::too_many_arguments)]
+    fn push_accessor(
+        &mut self,
+        bin: &mut Vec<u8>,
+        data: &[u8],
+        target: u32,
+        component_type: u32,
+        count: usize,
+        accessor_type: &str,
+        min_max: Option<(&[f32; 3], &[f32; 3])>,
+    ) -> usize {
+        // All of our components are 4 bytes, so this keeps every accessor aligned.
+        let byte_offset = bin.len();
+        bin.extend_from_slice(data);
+
+        let buffer_view = self.buffer_views.len();
+        self.buffer_views.push(format!(
+            r#"{{"buffer":0,"byteOffset":{byte_offset},"byteLength":{},"target":{target}}}"#,
+            data.len()
+        ));
+
+        let mut accessor = format!(
+            r#"{{"bufferView":{buffer_view},"componentType":{component_type},"count":{count},"type":"{accessor_type}""#
+        );
+        if let Some(([min_x, min_y, min_z], [max_x, max_y, max_z])) = min_max {
+            write!(
+                accessor,
+                r#","min":[{min_x},{min_y},{min_z}],"max":[{max_x},{max_y},{max_z}]"#
+            )
+            .unwrap();
+        }
+        accessor.push('}');
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn finish(self, buffer_length: usize) -> String {
+        let mut json = String::from(r#"{"asset":{"version":"2.0","generator":"block-mesh"}"#);
+        if !self.nodes.is_empty() {
+            let scene_nodes: Vec<String> = (0..self.nodes.len()).map(|i| i.to_string()).collect();
+            write!(
+                json,
+                r#","scene":0,"scenes":[{{"nodes":[{}]}}]"#,
+                scene_nodes.join(",")
+            )
+            .unwrap();
+        }
+        // Empty top-level arrays aren't allowed, so they're left out.
+        for (name, elements) in [
+            ("nodes", &self.nodes),
+            ("meshes", &self.meshes),
+            ("materials", &self.materials),
+            ("accessors", &self.accessors),
+            ("bufferViews", &self.buffer_views),
+        ] {
+            if !elements.is_empty() {
+                write!(json, r#","{name}":[{}]"#, elements.join(",")).unwrap();
+            }
+        }
+        if buffer_length > 0 {
+            write!(json, r#","buffers":[{{"byteLength":{buffer_length}}}]"#).unwrap();
+        }
+        json.push('}');
+        json
+    }
+}
+
+/// Writes the GLB header and chunks. Each chunk is padded to a multiple of 4 bytes, with spaces for JSON and zeros for the
+/// binary buffer.
+fn write_container(writer: &mut impl Write, json: &[u8], bin: &[u8]) -> io::Result<()> {
+    let padded = |len: usize| (len + 3) & !3;
+    let json_length = padded(json.len());
+    let bin_length = padded(bin.len());
+    let mut total_length = 12 + 8 + json_length;
+    if !bin.is_empty() {
+        total_length += 8 + bin_length;
+    }
+
+    for word in [GLB_MAGIC, GLB_VERSION, total_length as u32] {
+        writer.write_all(&word.to_le_bytes())?;
+    }
+
+    for word in [json_length as u32, CHUNK_TYPE_JSON] {
+        writer.write_all(&word.to_le_bytes())?;
+    }
+    writer.write_all(json)?;
+    writer.write_all(&b"   "[..json_length - json.len()])?;
+
+    if !bin.is_empty() {
+        for word in [bin_length as u32, CHUNK_TYPE_BIN] {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+        writer.write_all(bin)?;
+        writer.write_all(&[0; 3][..bin_length - bin.len()])?;
+    }
+    Ok(())
+}
+
+fn bounds(positions: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for p in positions {
+        for i in 0..3 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+    (min, max)
+}
+
+fn f32_bytes<const N: usize>(values: &[[f32; N]]) -> Vec<u8> {
+    values
+        .iter()
+        .flatten()
+        .flat_map(|c| c.to_le_bytes())
+        .collect()
+}
+
+fn u32_bytes(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|i| i.to_le_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{UnitQuadBuffer, UnorientedUnitQuad, RIGHT_HANDED_Z_UP_CONFIG};
+
+    use serde_json::Value;
+
+    fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// Parses the GLB container, checking its structure, and returns the JSON and binary chunks.
+    fn parse_glb(glb: &[u8]) -> (Value, Vec<u8>) {
+        assert_eq!(read_u32(glb, 0), GLB_MAGIC);
+        assert_eq!(read_u32(glb, 4), GLB_VERSION);
+        assert_eq!(read_u32(glb, 8) as usize, glb.len());
+
+        let json_length = read_u32(glb, 12) as usize;
+        assert_eq!(read_u32(glb, 16), CHUNK_TYPE_JSON);
+        assert_eq!(json_length % 4, 0);
+        let json = serde_json::from_slice(&glb[20..20 + json_length]).unwrap();
+
+        let rest = &glb[20 + json_length..];
+        if rest.is_empty() {
+            return (json, Vec::new());
+        }
+        let bin_length = read_u32(rest, 0) as usize;
+        assert_eq!(read_u32(rest, 4), CHUNK_TYPE_BIN);
+        assert_eq!(bin_length % 4, 0);
+        assert_eq!(rest.len(), 8 + bin_length);
+        (json, rest[8..].to_vec())
+    }
+
+    fn index(value: &Value) -> usize {
+        value.as_u64().unwrap() as usize
+    }
+
+    /// Returns the accessor's data as `f32` or `u32` components, after checking that it's in bounds.
+    fn accessor_components(json: &Value, bin: &[u8], accessor: usize) -> Vec<[u8; 4]> {
+        let accessor = &json["accessors"][accessor];
+        let view = &json["bufferViews"][index(&accessor["bufferView"])];
+        let num_components = match accessor["type"].as_str().unwrap() {
+            "SCALAR" => 1,
+            "VEC2" => 2,
+            "VEC3" => 3,
+            t => panic!("unexpected accessor type {t}"),
+        };
+        let offset = index(&view["byteOffset"]);
+        let length = index(&view["byteLength"]);
+        assert_eq!(offset % 4, 0);
+        assert_eq!(length, 4 * num_components * index(&accessor["count"]));
+        assert!(offset + length <= index(&json["buffers"][0]["byteLength"]));
+        bin[offset..offset + length]
+            .chunks(4)
+            .map(|c| c.try_into().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn writes_valid_glb() {
+        let mut cube = UnitQuadBuffer::new();
+        for group in cube.groups.iter_mut() {
+            group.push(UnorientedUnitQuad { minimum: [1, 2, 3] });
+        }
+        let empty = UnitQuadBuffer::new();
+        let chunks = [
+            GlbChunk {
+                quads: &cube,
+                translation: [0.0, 0.0, 0.0],
+            },
+            GlbChunk {
+                quads: &empty,
+                translation: [16.0, 0.0, 0.0],
+            },
+            GlbChunk {
+                quads: &cube,
+                translation: [0.0, -16.0, 32.0],
+            },
+        ];
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG).voxel_size(0.5);
+
+        let mut glb = Vec::new();
+        write_glb(
+            &mut glb,
+            &builder,
+            &chunks,
+            |chunk, group, _| (chunk + group) as u32 % 3,
+            |id| [id as f32 / 2.0, 0.0, 0.0, 1.0],
+        )
+        .unwrap();
+        let (json, bin) = parse_glb(&glb);
+
+        assert_eq!(json["asset"]["version"], "2.0");
+        assert_eq!(json["scenes"][0]["nodes"].as_array().unwrap().len(), 3);
+        let nodes = json["nodes"].as_array().unwrap();
+        assert!(nodes[1].get("mesh").is_none());
+        assert_eq!(nodes[2]["translation"], serde_json::json!([0, -16, 32]));
+        assert_eq!(json["meshes"].as_array().unwrap().len(), 2);
+        let materials = json["materials"].as_array().unwrap();
+        assert_eq!(materials.len(), 3);
+        assert_eq!(materials[0]["name"], "material_0");
+
+        let mut num_quads = 0;
+        for mesh in json["meshes"].as_array().unwrap() {
+            for primitive in mesh["primitives"].as_array().unwrap() {
+                assert!(index(&primitive["material"]) < materials.len());
+                let attributes = &primitive["attributes"];
+
+                let positions: Vec<f32> =
+                    accessor_components(&json, &bin, index(&attributes["POSITION"]))
+                        .into_iter()
+                        .map(f32::from_le_bytes)
+                        .collect();
+                let num_vertices = positions.len() / 3;
+                let position_accessor = &json["accessors"][index(&attributes["POSITION"])];
+                for i in 0..3 {
+                    let values = positions.iter().skip(i).step_by(3);
+                    let min = values.clone().copied().fold(f32::INFINITY, f32::min);
+                    let max = values.copied().fold(f32::NEG_INFINITY, f32::max);
+                    assert_eq!(position_accessor["min"][i].as_f64().unwrap() as f32, min);
+                    assert_eq!(position_accessor["max"][i].as_f64().unwrap() as f32, max);
+                }
+
+                let normals = accessor_components(&json, &bin, index(&attributes["NORMAL"]));
+                assert_eq!(normals.len(), 3 * num_vertices);
+                let tex_coords = accessor_components(&json, &bin, index(&attributes["TEXCOORD_0"]));
+                assert_eq!(tex_coords.len(), 2 * num_vertices);
+
+                let indices = accessor_components(&json, &bin, index(&primitive["indices"]));
+                assert!(indices
+                    .into_iter()
+                    .all(|i| (u32::from_le_bytes(i) as usize) < num_vertices));
+                num_quads += num_vertices / 4;
+            }
+        }
+        assert_eq!(num_quads, 12);
+    }
+
+    #[test]
+    fn writes_empty_glb() {
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG);
+        let chunks: [GlbChunk<UnitQuadBuffer>; 0] = [];
+        let mut glb = Vec::new();
+        write_glb(&mut glb, &builder, &chunks, |_, _, _| 0, |_| [1.0; 4]).unwrap();
+        let (json, bin) = parse_glb(&glb);
+
+        assert_eq!(json["asset"]["version"], "2.0");
+        assert!(json.get("buffers").is_none());
+        assert!(bin.is_empty());
+    }
+
+    #[test]
+    fn rejects_other_coordinate_systems() {
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Z_UP_CONFIG);
+        let chunks: [GlbChunk<UnitQuadBuffer>; 0] = [];
+        let mut glb = Vec::new();
+        let error = write_glb(&mut glb, &builder, &chunks, |_, _, _| 0, |_| [1.0; 4]).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(glb.is_empty());
+    }
+}
@@ -0,0 +1,188 @@
+use super::material_name;
+use crate::{MeshBuilder, OrientedBlockFace, QuadGroups, UnorientedQuad};
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// Writes `quads` as a Wavefront OBJ file, using the coordinate system, voxel size, and V flip of `builder`.
+///
+/// `material` is called with the group index and each quad to get its material ID. This is usually derived from the merge
+/// value of the quad's minimum voxel. Quads are grouped by material, and every quad is written as a single polygon.
+///
+/// If `mtl_file_name` is given, the OBJ file references it with `mtllib`, and it should be written with [`write_mtl`].
+///
+/// Returns the sorted IDs of all materials that were used.
+///
+/// ```
+/// # use block_mesh::{write_mtl, write_obj, MeshBuilder, QuadBuffer, RIGHT_HANDED_Y_UP_CONFIG};
+/// # let quads = QuadBuffer::new();
+/// let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG);
+/// let mut obj = Vec::new();
+/// let materials = write_obj(&mut obj, &builder, &quads, Some("chunk.mtl"), |_group, _quad| 0)?;
+/// let mut mtl = Vec::new();
+/// write_mtl(&mut mtl, &materials, |_id| [0.5, 0.5, 0.5])?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn write_obj(
+    writer: &mut impl Write,
+    builder: &MeshBuilder,
+    quads: &impl QuadGroups,
+    mtl_file_name: Option<&str>,
+    mut material: impl FnMut(usize, &UnorientedQuad) -> u32,
+) -> io::Result<Vec<u32>> {
+    let mut quads_by_material = BTreeMap::<u32, Vec<(usize, UnorientedQuad)>>::new();
+    quads.for_each_quad(|group_index, quad| {
+        quads_by_material
+            .entry(material(group_index, &quad))
+            .or_default()
+            .push((group_index, quad));
+    });
+
+    let faces = &builder.config.faces;
+
+    writeln!(writer, "# Generated by block-mesh")?;
+    if let Some(mtl_file_name) = mtl_file_name {
+        writeln!(writer, "mtllib {mtl_file_name}")?;
+    }
+    for face in faces.iter() {
+        let [x, y, z] = face.signed_normal().to_array();
+        writeln!(writer, "vn {x} {y} {z}")?;
+    }
+
+    let mut num_vertices = 0;
+    for (id, quads) in quads_by_material.iter() {
+        writeln!(writer, "usemtl {}", material_name(*id))?;
+        for (group_index, quad) in quads.iter() {
+            let face = &faces[*group_index];
+            for [x, y, z] in face.quad_mesh_positions(quad, builder.voxel_size) {
+                writeln!(writer, "v {x} {y} {z}")?;
+            }
+            for [u, v] in face.tex_coords(builder.config.u_flip_face, builder.flip_v, quad) {
+                writeln!(writer, "vt {u} {v}")?;
+            }
+
+            // OBJ indices start at 1.
+            let normal = group_index + 1;
+            write!(writer, "f")?;
+            for corner in polygon_corners(face) {
+                let vertex = num_vertices + corner + 1;
+                write!(writer, " {vertex}/{vertex}/{normal}")?;
+            }
+            writeln!(writer)?;
+            num_vertices += 4;
+        }
+    }
+
+    Ok(quads_by_material.into_keys().collect())
+}
+
+/// Writes a Wavefront MTL file with a material for each of `materials`, as named by [`material_name`].
+///
+/// `diffuse_color` is called with each material ID to get its RGB color in `[0, 1]`.
+pub fn write_mtl(
+    writer: &mut impl Write,
+    materials: &[u32],
+    mut diffuse_color: impl FnMut(u32) -> [f32; 3],
+) -> io::Result<()> {
+    writeln!(writer, "# Generated by block-mesh")?;
+    for &id in materials {
+        let [r, g, b] = diffuse_color(id);
+        writeln!(writer)?;
+        writeln!(writer, "newmtl {}", material_name(id))?;
+        writeln!(writer, "Kd {r} {g} {b}")?;
+    }
+    Ok(())
+}
+
+/// The corners of a quad in counterclockwise order around its front face, as indices into
+/// [`OrientedBlockFace::quad_corners`].
+fn polygon_corners(face: &OrientedBlockFace) -> [usize; 4] {
+    if face.n_sign() * face.permutation().sign() > 0 {
+        [0, 1, 3, 2]
+    } else {
+        [0, 2, 3, 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{QuadBuffer, UnitQuadBuffer, UnorientedUnitQuad, RIGHT_HANDED_Y_UP_CONFIG};
+
+    use ilattice::glam::Vec3;
+
+    fn parse_vectors(obj: &str, prefix: &str) -> Vec<Vec3> {
+        obj.lines()
+            .filter_map(|line| line.strip_prefix(prefix))
+            .map(|rest| {
+                let c: Vec<f32> = rest.split(' ').map(|c| c.parse().unwrap()).collect();
+                Vec3::new(c[0], c[1], c.get(2).copied().unwrap_or(0.0))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn writes_unit_cube() {
+        let mut quads = UnitQuadBuffer::new();
+        for group in quads.groups.iter_mut() {
+            group.push(UnorientedUnitQuad { minimum: [0; 3] });
+        }
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG).voxel_size(2.0);
+
+        let mut obj = Vec::new();
+        let materials = write_obj(&mut obj, &builder, &quads, Some("cube.mtl"), |group, _| {
+            group as u32 % 2
+        })
+        .unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+
+        assert_eq!(materials, [0, 1]);
+        assert!(obj.contains("mtllib cube.mtl\n"));
+        assert_eq!(obj.matches("usemtl ").count(), 2);
+        let positions = parse_vectors(&obj, "v ");
+        assert_eq!(positions.len(), 24);
+        assert!(positions
+            .iter()
+            .all(|p| p.min_element() >= 0.0 && p.max_element() <= 2.0));
+        assert_eq!(parse_vectors(&obj, "vt ").len(), 24);
+        let normals = parse_vectors(&obj, "vn ");
+
+        // Every polygon is counterclockwise around its outward normal.
+        let faces: Vec<_> = obj
+            .lines()
+            .filter_map(|line| line.strip_prefix("f "))
+            .collect();
+        assert_eq!(faces.len(), 6);
+        for face in faces {
+            let corners: Vec<Vec<usize>> = face
+                .split(' ')
+                .map(|corner| corner.split('/').map(|i| i.parse().unwrap()).collect())
+                .collect();
+            let [a, b, c] = [0, 1, 2].map(|i| positions[corners[i][0] - 1]);
+            let normal = normals[corners[0][2] - 1];
+            assert!((b - a).cross(c - a).dot(normal) > 0.0);
+            // The center of the cube is behind every face.
+            assert!((Vec3::ONE - a).dot(normal) < 0.0);
+        }
+    }
+
+    #[test]
+    fn writes_mtl() {
+        let mut mtl = Vec::new();
+        write_mtl(&mut mtl, &[3, 7], |id| [id as f32 / 10.0, 0.0, 1.0]).unwrap();
+        let mtl = String::from_utf8(mtl).unwrap();
+
+        assert!(mtl.contains("newmtl material_3\nKd 0.3 0 1\n"));
+        assert!(mtl.contains("newmtl material_7\nKd 0.7 0 1\n"));
+    }
+
+    #[test]
+    fn writes_empty_quad_buffer() {
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG);
+        let mut obj = Vec::new();
+        let materials = write_obj(&mut obj, &builder, &QuadBuffer::new(), None, |_, _| 0).unwrap();
+
+        assert!(materials.is_empty());
+        assert!(!String::from_utf8(obj).unwrap().contains("mtllib"));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{MeshBuffer, MeshBuilder, QuadGroups, UnorientedQuad};
+
+use std::io::{self, Write};
+
+/// The encoding of a PLY file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+}
+
+/// Writes `quads` as a PLY triangle mesh, using the coordinate system and voxel size of `builder`.
+///
+/// Every vertex has a position and normal.
+pub fn write_ply(
+    writer: &mut impl Write,
+    builder: &MeshBuilder,
+    quads: &impl QuadGroups,
+    format: PlyFormat,
+) -> io::Result<()> {
+    write_ply_mesh(writer, &builder.build(quads), None, format)
+}
+
+/// Same as [`write_ply`], but every vertex also has a color.
+///
+/// `color` is called with the group index and each quad to get the RGB color of the quad's vertices, which is usually
+/// derived from the quad's minimum voxel.
+pub fn write_ply_with_colors(
+    writer: &mut impl Write,
+    builder: &MeshBuilder,
+    quads: &impl QuadGroups,
+    format: PlyFormat,
+    mut color: impl FnMut(usize, &UnorientedQuad) -> [u8; 3],
+) -> io::Result<()> {
+    let mut colors = Vec::with_capacity(quads.num_quads());
+    quads.for_each_quad(|group_index, quad| colors.push(color(group_index, &quad)));
+    write_ply_mesh(writer, &builder.build(quads), Some(&colors), format)
+}
+
+/// Writes `mesh`, with one color per quad if `colors` is given.
+fn write_ply_mesh(
+    writer: &mut impl Write,
+    mesh: &MeshBuffer,
+    colors: Option<&[[u8; 3]]>,
+    format: PlyFormat,
+) -> io::Result<()> {
+    writeln!(writer, "ply")?;
+    match format {
+        PlyFormat::Ascii => writeln!(writer, "format ascii 1.0")?,
+        PlyFormat::BinaryLittleEndian => writeln!(writer, "format binary_little_endian 1.0")?,
+    }
+    writeln!(writer, "comment Generated by block-mesh")?;
+    writeln!(writer, "element vertex {}", mesh.num_vertices())?;
+    for property in ["x", "y", "z", "nx", "ny", "nz"] {
+        writeln!(writer, "property float {property}")?;
+    }
+    if colors.is_some() {
+        for property in ["red", "green", "blue"] {
+            writeln!(writer, "property uchar {property}")?;
+        }
+    }
+    writeln!(writer, "element face {}", mesh.num_triangles())?;
+    writeln!(writer, "property list uchar uint vertex_indices")?;
+    writeln!(writer, "end_header")?;
+
+    for (i, (position, normal)) in mesh.positions.iter().zip(mesh.normals.iter()).enumerate() {
+        // Each quad has 4 vertices of the same color.
+        let color = colors.map(|colors| colors[i / 4]);
+        match format {
+            PlyFormat::Ascii => {
+                let [x, y, z] = position;
+                let [nx, ny, nz] = normal;
+                write!(writer, "{x} {y} {z} {nx} {ny} {nz}")?;
+                if let Some([r, g, b]) = color {
+                    write!(writer, " {r} {g} {b}")?;
+                }
+                writeln!(writer)?;
+            }
+            PlyFormat::BinaryLittleEndian => {
+                for c in position.iter().chain(normal.iter()) {
+                    writer.write_all(&c.to_le_bytes())?;
+                }
+                if let Some(color) = color {
+                    writer.write_all(&color)?;
+                }
+            }
+        }
+    }
+
+    for [a, b, c] in mesh.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]) {
+        match format {
+            PlyFormat::Ascii => writeln!(writer, "3 {a} {b} {c}")?,
+            PlyFormat::BinaryLittleEndian => {
+                writer.write_all(&[3])?;
+                for i in [a, b, c] {
+                    writer.write_all(&i.to_le_bytes())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{UnitQuadBuffer, UnorientedUnitQuad, RIGHT_HANDED_Y_UP_CONFIG};
+
+    fn cube() -> UnitQuadBuffer {
+        let mut quads = UnitQuadBuffer::new();
+        for group in quads.groups.iter_mut() {
+            group.push(UnorientedUnitQuad { minimum: [0; 3] });
+        }
+        quads
+    }
+
+    fn split_header(ply: &[u8]) -> (String, &[u8]) {
+        let end = b"end_header\n";
+        let header_length = ply.windows(end.len()).position(|w| w == end).unwrap() + end.len();
+        (
+            String::from_utf8(ply[..header_length].to_vec()).unwrap(),
+            &ply[header_length..],
+        )
+    }
+
+    #[test]
+    fn writes_ascii_ply_with_colors() {
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG);
+        let mut ply = Vec::new();
+        write_ply_with_colors(&mut ply, &builder, &cube(), PlyFormat::Ascii, |group, _| {
+            [group as u8 * 10, 0, 255]
+        })
+        .unwrap();
+        let (header, body) = split_header(&ply);
+
+        assert!(header.starts_with("ply\nformat ascii 1.0\n"));
+        assert!(header.contains("element vertex 24\n"));
+        assert!(header.contains("property uchar red\n"));
+        assert!(header.contains("element face 12\n"));
+        let lines: Vec<&str> = std::str::from_utf8(body).unwrap().lines().collect();
+        assert_eq!(lines.len(), 24 + 12);
+        // The first vertex of the +Y quad.
+        assert!(lines[16].ends_with(" 40 0 255"));
+        assert!(lines[24..].iter().all(|line| line.starts_with("3 ")));
+    }
+
+    #[test]
+    fn writes_binary_ply() {
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG);
+        let mut ply = Vec::new();
+        write_ply(&mut ply, &builder, &cube(), PlyFormat::BinaryLittleEndian).unwrap();
+        let (header, body) = split_header(&ply);
+
+        assert!(header.starts_with("ply\nformat binary_little_endian 1.0\n"));
+        assert!(!header.contains("red"));
+        assert_eq!(body.len(), 24 * 6 * 4 + 12 * (1 + 3 * 4));
+        // The first face starts right after the vertices.
+        assert_eq!(body[24 * 6 * 4], 3);
+    }
+}
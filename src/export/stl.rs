@@ -0,0 +1,95 @@
+use crate::{MeshBuilder, QuadGroups};
+
+use std::io::{self, Write};
+
+/// Writes `quads` as a binary STL file, using the coordinate system and voxel size of `builder`.
+///
+/// Every quad becomes two triangles, wound counterclockwise around their outward facet normal as
+/// [`OrientedBlockFace::quad_mesh_indices`](crate::OrientedBlockFace::quad_mesh_indices) determines. Most slicers expect
+/// all coordinates to be positive, so the quads should come from an extent with a non-negative minimum.
+///
+/// STL coordinates are right-handed. Every configuration winds triangles by the right-hand rule, so the winding is
+/// outward for all of them, but a mesh built with a left-handed configuration is read as its mirror image. Use a
+/// right-handed configuration to keep the model's handedness, usually
+/// [`RIGHT_HANDED_Z_UP_CONFIG`](crate::RIGHT_HANDED_Z_UP_CONFIG) for slicers.
+pub fn write_stl(
+    writer: &mut impl Write,
+    builder: &MeshBuilder,
+    quads: &impl QuadGroups,
+) -> io::Result<()> {
+    let mesh = builder.build(quads);
+
+    let mut header = [0; 80];
+    let comment = b"Generated by block-mesh";
+    header[..comment.len()].copy_from_slice(comment);
+    writer.write_all(&header)?;
+    writer.write_all(&(mesh.num_triangles() as u32).to_le_bytes())?;
+
+    for triangle in mesh.indices.chunks_exact(3) {
+        // All vertices of a quad share the same normal.
+        let normal = mesh.normals[triangle[0] as usize];
+        for c in normal.iter().chain(
+            triangle
+                .iter()
+                .flat_map(|&i| mesh.positions[i as usize].iter()),
+        ) {
+            writer.write_all(&c.to_le_bytes())?;
+        }
+        // Attribute byte count.
+        writer.write_all(&[0; 2])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        UnitQuadBuffer, UnorientedUnitQuad, LEFT_HANDED_Y_UP_CONFIG, LEFT_HANDED_Z_UP_CONFIG,
+        RIGHT_HANDED_Y_UP_CONFIG, RIGHT_HANDED_Z_UP_CONFIG,
+    };
+
+    use ilattice::glam::Vec3;
+
+    #[test]
+    fn writes_outward_wound_cube() {
+        let mut quads = UnitQuadBuffer::new();
+        for group in quads.groups.iter_mut() {
+            group.push(UnorientedUnitQuad { minimum: [0; 3] });
+        }
+        for config in [
+            &RIGHT_HANDED_Y_UP_CONFIG,
+            &LEFT_HANDED_Y_UP_CONFIG,
+            &RIGHT_HANDED_Z_UP_CONFIG,
+            &LEFT_HANDED_Z_UP_CONFIG,
+        ] {
+            let builder = MeshBuilder::new(config).voxel_size(10.0);
+
+            let mut stl = Vec::new();
+            write_stl(&mut stl, &builder, &quads).unwrap();
+
+            assert!(stl.starts_with(b"Generated by block-mesh"));
+            let num_triangles = u32::from_le_bytes(stl[80..84].try_into().unwrap()) as usize;
+            assert_eq!(num_triangles, 12);
+            assert_eq!(stl.len(), 84 + 50 * num_triangles);
+
+            let center = Vec3::splat(5.0);
+            for facet in stl[84..].chunks(50) {
+                let v: Vec<Vec3> = facet[..48]
+                    .chunks(12)
+                    .map(|c| {
+                        let c: Vec<f32> = c
+                            .chunks(4)
+                            .map(|f| f32::from_le_bytes(f.try_into().unwrap()))
+                            .collect();
+                        Vec3::new(c[0], c[1], c[2])
+                    })
+                    .collect();
+                let (normal, [a, b, c]) = (v[0], [v[1], v[2], v[3]]);
+                assert!((b - a).cross(c - a).dot(normal) > 0.0);
+                assert!((a - center).dot(normal) > 0.0);
+                assert_eq!(facet[48..], [0, 0]);
+            }
+        }
+    }
+}
@@ -113,17 +113,19 @@
 mod axis;
 mod face;
 mod quad;
+mod transform;
 
 pub use axis::*;
 pub use face::*;
 pub use quad::*;
+pub use transform::*;
 
 /// A configuration of XYZ --> NUV axis mappings and orientations of the cube
 /// faces for a given coordinate system.
 ///
 /// See the [`geometry` module documentation][crate::geometry] for more
 /// information on `{N, U, V}` space.
-#[derive(Clone)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct QuadCoordinateConfig {
     pub faces: [OrientedBlockFace; 6],
 
@@ -158,7 +160,179 @@ pub struct QuadCoordinateConfig {
     pub u_flip_face: Axis,
 }
 
-/// Coordinate configuration for a right-handed coordinate system with Y up.
+/// The [handedness](crate::geometry#handedness) of a coordinate system.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Handedness {
+    Left,
+    Right,
+}
+
+impl QuadCoordinateConfig {
+    /// Derives the configuration for a coordinate system from its handedness
+    /// and the direction that textures should point up on the side faces.
+    ///
+    /// The faces are in the order -X, -Y, -Z, +X, +Y, +Z, like the presets,
+    /// which are all equal to the result of this function:
+    ///
+    /// ```
+    /// # use block_mesh::*;
+    /// assert_eq!(
+    ///     QuadCoordinateConfig::new(Handedness::Right, SignedAxis::PosY),
+    ///     RIGHT_HANDED_Y_UP_CONFIG
+    /// );
+    /// assert_eq!(
+    ///     QuadCoordinateConfig::new(Handedness::Left, SignedAxis::PosZ),
+    ///     LEFT_HANDED_Z_UP_CONFIG
+    /// );
+    /// ```
+    ///
+    /// V always runs toward the positive end of an axis, so when `up` is
+    /// negative, the configuration is meant for textures with (0, 0) at the
+    /// top left, like [`RIGHT_HANDED_Y_DOWN_CONFIG`].
+    pub fn new(handedness: Handedness, up: SignedAxis) -> Self {
+        let up_axis = up.unsigned_axis();
+        // The sign that every face's U x V must have along its outward normal
+        // for textures not to be mirrored. See `face_mirror_sign`.
+        let texture_sign = match handedness {
+            Handedness::Right => up.signum(),
+            Handedness::Left => -up.signum(),
+        };
+
+        // Side faces have V on the up axis. Exactly one of the two side axes
+        // needs its U flipped to give every side face the right sign.
+        let side_permutation = |normal_axis: Axis| {
+            let third = third_axis(normal_axis, up_axis);
+            permutation_with_axes([normal_axis, third, up_axis])
+        };
+        let u_flip_face = [Axis::X, Axis::Y, Axis::Z]
+            .into_iter()
+            .find(|&axis| axis != up_axis && side_permutation(axis).sign() != texture_sign)
+            .unwrap();
+
+        // The up and down faces never have their U flipped, so they only need
+        // a permutation with the right sign.
+        let up_permutation = if texture_sign > 0 {
+            AxisPermutation::even_with_normal_axis(up_axis)
+        } else {
+            AxisPermutation::odd_with_normal_axis(up_axis)
+        };
+
+        let faces = [-1, 1].map(|n_sign| {
+            [Axis::X, Axis::Y, Axis::Z].map(|axis| {
+                let permutation = if axis == up_axis {
+                    up_permutation
+                } else {
+                    side_permutation(axis)
+                };
+                OrientedBlockFace::new(n_sign, permutation)
+            })
+        });
+        let [[nx, ny, nz], [px, py, pz]] = faces;
+
+        Self {
+            faces: [nx, ny, nz, px, py, pz],
+            u_flip_face,
+        }
+    }
+
+    /// Checks that the faces have 6 different normals and that none of them
+    /// show mirrored textures relative to the others.
+    ///
+    /// Inconsistent configurations give meshes with missing faces, or with
+    /// textures that are mirrored on some faces. All of the presets and
+    /// configurations from [`QuadCoordinateConfig::new`] are valid.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for (i, face) in self.faces.iter().enumerate() {
+            let normal = face.normal_axis();
+            if self.faces[..i].iter().any(|f| f.normal_axis() == normal) {
+                return Err(ConfigError::DuplicateNormal(normal));
+            }
+        }
+
+        let signs = self
+            .faces
+            .map(|face| face_mirror_sign(&face, self.u_flip_face));
+        if signs.iter().all(|&s| s == signs[0]) {
+            return Ok(());
+        }
+        // A different `u_flip_face` may be all that's wrong.
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let signs = self.faces.map(|face| face_mirror_sign(&face, axis));
+            if signs.iter().all(|&s| s == signs[0]) {
+                return Err(ConfigError::WrongUFlipFace { expected: axis });
+            }
+        }
+        // Report the face that disagrees with the majority.
+        let num_positive = signs.iter().filter(|&&s| s > 0).count();
+        let majority = if num_positive >= 3 { 1 } else { -1 };
+        let i = signs.iter().position(|&s| s != majority).unwrap();
+        Err(ConfigError::MirroredFace(self.faces[i].normal_axis()))
+    }
+}
+
+/// An error from [`QuadCoordinateConfig::validate`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+    /// More than one face has this normal.
+    DuplicateNormal(SignedAxis),
+    /// The faces are consistent, but only with a different `u_flip_face`.
+    WrongUFlipFace { expected: Axis },
+    /// The face with this normal shows textures mirrored relative to the
+    /// other faces.
+    MirroredFace(SignedAxis),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateNormal(normal) => {
+                write!(f, "more than one face has the normal {normal:?}")
+            }
+            Self::WrongUFlipFace { expected } => {
+                write!(f, "u_flip_face should be {expected:?}")
+            }
+            Self::MirroredFace(normal) => {
+                write!(f, "the {normal:?} face mirrors textures")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// The sign of U x V along the outward normal of `face`, with the direction
+/// of U given by [`OrientedBlockFace::flip_u`]. Textures are mirrored on
+/// faces where this differs.
+fn face_mirror_sign(face: &OrientedBlockFace, u_flip_face: Axis) -> i32 {
+    let u_sign = if face.flip_u(u_flip_face) { -1 } else { 1 };
+    u_sign * face.permutation().sign() * face.n_sign()
+}
+
+/// The axis that is neither `a` nor `b`, which must differ.
+fn third_axis(a: Axis, b: Axis) -> Axis {
+    [Axis::X, Axis::Y, Axis::Z]
+        .into_iter()
+        .find(|&axis| axis != a && axis != b)
+        .unwrap()
+}
+
+/// The permutation of `axes`, which must all differ.
+fn permutation_with_axes(axes: [Axis; 3]) -> AxisPermutation {
+    [
+        AxisPermutation::Xyz,
+        AxisPermutation::Zxy,
+        AxisPermutation::Yzx,
+        AxisPermutation::Zyx,
+        AxisPermutation::Xzy,
+        AxisPermutation::Yxz,
+    ]
+    .into_iter()
+    .find(|permutation| permutation.axes() == axes)
+    .unwrap()
+}
+
+/// Coordinate configuration for a right-handed coordinate system with Y up, as
+/// used by Bevy, Godot, and OpenGL.
 ///
 /// ```text
 ///       +Y      
@@ -181,3 +355,238 @@ pub const RIGHT_HANDED_Y_UP_CONFIG: QuadCoordinateConfig = QuadCoordinateConfig
     ],
     u_flip_face: Axis::X,
 };
+
+/// Coordinate configuration for a left-handed coordinate system with Y up, as
+/// used by Unity.
+///
+/// ```text
+///       +Y
+///       | +Z
+/// -X____|/____+X
+///      /|
+///    -Z |
+///       -Y
+/// ```
+///
+/// Triangles are still wound counterclockwise around their outward normals
+/// by the right-hand rule, which looks clockwise in a left-handed system.
+/// That is the front face winding that Unity expects.
+pub const LEFT_HANDED_Y_UP_CONFIG: QuadCoordinateConfig = QuadCoordinateConfig {
+    // Y is always in the V direction when it's not the normal. When Y is the
+    // normal, left-handedness determines that we must use Yxz permutations.
+    faces: [
+        OrientedBlockFace::new(-1, AxisPermutation::Xzy),
+        OrientedBlockFace::new(-1, AxisPermutation::Yxz),
+        OrientedBlockFace::new(-1, AxisPermutation::Zxy),
+        OrientedBlockFace::new(1, AxisPermutation::Xzy),
+        OrientedBlockFace::new(1, AxisPermutation::Yxz),
+        OrientedBlockFace::new(1, AxisPermutation::Zxy),
+    ],
+    u_flip_face: Axis::Z,
+};
+
+/// Coordinate configuration for a right-handed coordinate system with Z up,
+/// as used by Blender.
+///
+/// ```text
+///       +Z
+///       | +Y
+/// -X____|/____+X
+///      /|
+///    -Y |
+///       -Z
+/// ```
+pub const RIGHT_HANDED_Z_UP_CONFIG: QuadCoordinateConfig = QuadCoordinateConfig {
+    // Z is always in the V direction when it's not the normal. When Z is the
+    // normal, right-handedness determines that we must use Zxy permutations.
+    faces: [
+        OrientedBlockFace::new(-1, AxisPermutation::Xyz),
+        OrientedBlockFace::new(-1, AxisPermutation::Yxz),
+        OrientedBlockFace::new(-1, AxisPermutation::Zxy),
+        OrientedBlockFace::new(1, AxisPermutation::Xyz),
+        OrientedBlockFace::new(1, AxisPermutation::Yxz),
+        OrientedBlockFace::new(1, AxisPermutation::Zxy),
+    ],
+    u_flip_face: Axis::Y,
+};
+
+/// Coordinate configuration for a left-handed coordinate system with Z up, as
+/// used by Unreal Engine.
+///
+/// ```text
+///       +Z
+///       | +X
+/// -Y____|/____+Y
+///      /|
+///    -X |
+///       -Z
+/// ```
+///
+/// Like [`LEFT_HANDED_Y_UP_CONFIG`], triangles are wound counterclockwise
+/// around their outward normals by the right-hand rule.
+pub const LEFT_HANDED_Z_UP_CONFIG: QuadCoordinateConfig = QuadCoordinateConfig {
+    // Z is always in the V direction when it's not the normal. When Z is the
+    // normal, left-handedness determines that we must use Zyx permutations.
+    faces: [
+        OrientedBlockFace::new(-1, AxisPermutation::Xyz),
+        OrientedBlockFace::new(-1, AxisPermutation::Yxz),
+        OrientedBlockFace::new(-1, AxisPermutation::Zyx),
+        OrientedBlockFace::new(1, AxisPermutation::Xyz),
+        OrientedBlockFace::new(1, AxisPermutation::Yxz),
+        OrientedBlockFace::new(1, AxisPermutation::Zyx),
+    ],
+    u_flip_face: Axis::X,
+};
+
+/// Coordinate configuration for a right-handed coordinate system with Y down.
+///
+/// ```text
+///       -Y
+///       | -Z
+/// +X____|/____-X
+///      /|
+///    +Z |
+///       +Y
+/// ```
+///
+/// V still runs toward +Y, which is down. So this is meant for textures with
+/// (0, 0) at the top left, without
+/// [`MeshBuilder::flip_v`](crate::MeshBuilder::flip_v). Flipping the
+/// direction of V mirrors textures, so the faces are the same as in
+/// [`LEFT_HANDED_Y_UP_CONFIG`].
+pub const RIGHT_HANDED_Y_DOWN_CONFIG: QuadCoordinateConfig = LEFT_HANDED_Y_UP_CONFIG;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ilattice::glam::{IVec3, Vec3};
+
+    /// Checks the winding and texture orientation of every face of `config`.
+    ///
+    /// `handedness` is 1 for right-handed and -1 for left-handed coordinates,
+    /// and `up` is the direction that textures should point up on the side
+    /// faces. If `top_left_origin`, textures have (0, 0) at the top left.
+    fn check_config(
+        config: &QuadCoordinateConfig,
+        handedness: f32,
+        up: IVec3,
+        top_left_origin: bool,
+    ) {
+        let quad = UnorientedQuad {
+            minimum: [0; 3],
+            width: 1,
+            height: 1,
+        };
+
+        let mut normals: Vec<_> = config
+            .faces
+            .iter()
+            .map(|face| face.signed_normal().to_array())
+            .collect();
+        normals.sort();
+        normals.dedup();
+        assert_eq!(normals.len(), 6);
+
+        for face in config.faces.iter() {
+            let normal = face.signed_normal().as_vec3();
+
+            // Front faces are counterclockwise around the outward normal.
+            let positions = face.quad_mesh_positions(&quad, 1.0).map(Vec3::from);
+            for triangle in face.quad_mesh_indices(0).chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+                assert!((b - a).cross(c - a).dot(normal) > 0.0);
+            }
+
+            // The directions of the texture's +U and up in space.
+            let tex_coords = face.tex_coords(config.u_flip_face, false, &quad);
+            let du = tex_coords[1][0] - tex_coords[0][0];
+            let dv = tex_coords[2][1] - tex_coords[0][1];
+            let tex_u = (positions[1] - positions[0]) * du;
+            let mut tex_up = (positions[2] - positions[0]) * dv;
+            if top_left_origin {
+                tex_up = -tex_up;
+            }
+
+            // The texture isn't mirrored when seen from outside of the cube.
+            assert_eq!(tex_u.cross(tex_up).dot(normal), handedness);
+            // Side faces show the texture upright.
+            if normal.dot(up.as_vec3()) == 0.0 {
+                assert_eq!(tex_up, up.as_vec3());
+            }
+        }
+    }
+
+    #[test]
+    fn new_matches_presets() {
+        let presets = [
+            (
+                Handedness::Right,
+                SignedAxis::PosY,
+                &RIGHT_HANDED_Y_UP_CONFIG,
+            ),
+            (Handedness::Left, SignedAxis::PosY, &LEFT_HANDED_Y_UP_CONFIG),
+            (
+                Handedness::Right,
+                SignedAxis::PosZ,
+                &RIGHT_HANDED_Z_UP_CONFIG,
+            ),
+            (Handedness::Left, SignedAxis::PosZ, &LEFT_HANDED_Z_UP_CONFIG),
+            (
+                Handedness::Right,
+                SignedAxis::NegY,
+                &RIGHT_HANDED_Y_DOWN_CONFIG,
+            ),
+        ];
+        for (handedness, up, preset) in presets {
+            assert_eq!(QuadCoordinateConfig::new(handedness, up), *preset);
+            assert_eq!(preset.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn new_is_consistent_for_every_up_axis() {
+        for up in SignedAxis::ALL {
+            for (handedness, sign) in [(Handedness::Right, 1.0), (Handedness::Left, -1.0)] {
+                let config = QuadCoordinateConfig::new(handedness, up);
+                assert_eq!(config.validate(), Ok(()));
+                // Down axes use textures with the origin at the top left.
+                let top_left_origin = up.signum() < 0;
+                check_config(&config, sign, up.get_unit_vector(), top_left_origin);
+            }
+        }
+    }
+
+    #[test]
+    fn validate_rejects_inconsistent_configs() {
+        let mut config = RIGHT_HANDED_Y_UP_CONFIG;
+        config.faces[3] = config.faces[0];
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::DuplicateNormal(SignedAxis::NegX))
+        );
+
+        let mut config = RIGHT_HANDED_Y_UP_CONFIG;
+        config.u_flip_face = Axis::Z;
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::WrongUFlipFace { expected: Axis::X })
+        );
+
+        let mut config = RIGHT_HANDED_Y_UP_CONFIG;
+        config.faces[4] = OrientedBlockFace::new(1, AxisPermutation::Yxz);
+        assert_eq!(
+            config.validate(),
+            Err(ConfigError::MirroredFace(SignedAxis::PosY))
+        );
+    }
+
+    #[test]
+    fn presets_are_consistent() {
+        check_config(&RIGHT_HANDED_Y_UP_CONFIG, 1.0, IVec3::Y, false);
+        check_config(&LEFT_HANDED_Y_UP_CONFIG, -1.0, IVec3::Y, false);
+        check_config(&RIGHT_HANDED_Z_UP_CONFIG, 1.0, IVec3::Z, false);
+        check_config(&LEFT_HANDED_Z_UP_CONFIG, -1.0, IVec3::Z, false);
+        check_config(&RIGHT_HANDED_Y_DOWN_CONFIG, 1.0, -IVec3::Y, true);
+    }
+}
@@ -125,6 +125,16 @@ pub enum SignedAxis {
 }
 
 impl SignedAxis {
+    /// All six signed axes, in order of their discriminants.
+    pub const ALL: [Self; 6] = [
+        Self::NegX,
+        Self::PosX,
+        Self::NegY,
+        Self::PosY,
+        Self::NegZ,
+        Self::PosZ,
+    ];
+
     #[inline]
     pub fn new(sign: i32, axis: Axis) -> Self {
         assert!(sign != 0);
@@ -163,6 +173,19 @@ impl SignedAxis {
         }
     }
 
+    /// The axis pointing in the opposite direction.
+    #[inline]
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::NegX => Self::PosX,
+            Self::NegY => Self::PosY,
+            Self::NegZ => Self::PosZ,
+            Self::PosX => Self::NegX,
+            Self::PosY => Self::NegY,
+            Self::PosZ => Self::NegZ,
+        }
+    }
+
     #[inline]
     pub fn get_unit_vector(&self) -> IVec3 {
         match self {
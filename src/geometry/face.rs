@@ -1,6 +1,7 @@
 use crate::{Axis, AxisPermutation, SignedAxis, UnorientedQuad};
 
 use ilattice::glam::{IVec3, UVec3};
+use std::ops::Add;
 
 /// Metadata that's used to aid in the geometric calculations for one of the 6 possible cube faces.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -55,6 +56,12 @@ impl OrientedBlockFace {
         self.n.as_ivec3() * self.n_sign
     }
 
+    /// The outward normal of this face as a [`SignedAxis`].
+    #[inline]
+    pub fn normal_axis(&self) -> SignedAxis {
+        SignedAxis::new(self.n_sign, self.permutation.axes()[0])
+    }
+
     /// Returns the 4 corners of the quad in this order:
     ///
     /// ```text
@@ -89,6 +96,15 @@ impl OrientedBlockFace {
         [minu_minv, maxu_minv, minu_maxv, maxu_maxv]
     }
 
+    /// Same as [`OrientedBlockFace::quad_corners`], but in world space for a
+    /// voxel array whose `[0, 0, 0]` voxel is at `origin` in the world.
+    ///
+    /// See [`greedy_quads_in_world`](crate::greedy_quads_in_world).
+    #[inline]
+    pub fn quad_corners_in_world(&self, quad: &UnorientedQuad, origin: IVec3) -> [IVec3; 4] {
+        self.quad_corners(quad).map(|c| origin + c.as_ivec3())
+    }
+
     #[inline]
     pub fn quad_mesh_positions(&self, quad: &UnorientedQuad, voxel_size: f32) -> [[f32; 3]; 4] {
         self.quad_corners(quad)
@@ -111,6 +127,41 @@ impl OrientedBlockFace {
         quad_indices(start, self.n_sign * self.permutation.sign() > 0)
     }
 
+    /// Same as [`OrientedBlockFace::quad_mesh_indices`], but splits the quad
+    /// along the diagonal that interpolates the per-vertex `values` without
+    /// anisotropy artifacts. `values` are in the same order as
+    /// [`OrientedBlockFace::quad_corners`], like ambient occlusion or light
+    /// levels.
+    ///
+    /// The quad is split along the diagonal whose endpoints have the larger
+    /// sum, so a single dark corner shades both triangles symmetrically. If
+    /// the sums are equal, the diagonal of `quad_mesh_indices` is used.
+    #[inline]
+    pub fn quad_mesh_indices_with_corner_values<T>(&self, start: u32, values: [T; 4]) -> [u32; 6]
+    where
+        T: Copy + PartialOrd + Add<Output = T>,
+    {
+        let counter_clockwise = self.n_sign * self.permutation.sign() > 0;
+        if values[0] + values[3] > values[1] + values[2] {
+            flipped_quad_indices(start, counter_clockwise)
+        } else {
+            quad_indices(start, counter_clockwise)
+        }
+    }
+
+    /// Returns true iff the U texture coordinate of this face must run
+    /// towards -U to avoid mirroring the texture. See
+    /// [`QuadCoordinateConfig::u_flip_face`](crate::QuadCoordinateConfig::u_flip_face).
+    #[inline]
+    pub fn flip_u(&self, u_flip_face: Axis) -> bool {
+        let face_normal_axis = self.permutation.axes()[0];
+        if self.n_sign < 0 {
+            u_flip_face != face_normal_axis
+        } else {
+            u_flip_face == face_normal_axis
+        }
+    }
+
     /// Returns the UV coordinates of the 4 corners of the quad. Returns
     /// vertices in the same order as [`OrientedBlockFace::quad_corners`].
     ///
@@ -124,8 +175,9 @@ impl OrientedBlockFace {
     /// particular orientation for the texture. This should be sufficient for
     /// minecraft-style meshing.
     ///
-    /// If you need to use a texture atlas, you must calculate your own
-    /// coordinates from the `Quad`.
+    /// For texture atlases and array textures, see
+    /// [`atlas_uvs`](crate::atlas_uvs) and
+    /// [`array_texture_layers`](crate::array_texture_layers).
     #[inline]
     pub fn tex_coords(
         &self,
@@ -133,14 +185,7 @@ impl OrientedBlockFace {
         flip_v: bool,
         quad: &UnorientedQuad,
     ) -> [[f32; 2]; 4] {
-        let face_normal_axis = self.permutation.axes()[0];
-        let flip_u = if self.n_sign < 0 {
-            u_flip_face != face_normal_axis
-        } else {
-            u_flip_face == face_normal_axis
-        };
-
-        match (flip_u, flip_v) {
+        match (self.flip_u(u_flip_face), flip_v) {
             (false, false) => [
                 [0.0, 0.0],
                 [quad.width as f32, 0.0],
@@ -172,10 +217,58 @@ impl OrientedBlockFace {
 /// Returns the vertex indices for a single quad (two triangles). The triangles
 /// may have either clockwise or counter-clockwise winding. `start` is the first
 /// index.
-fn quad_indices(start: u32, counter_clockwise: bool) -> [u32; 6] {
+pub(crate) fn quad_indices(start: u32, counter_clockwise: bool) -> [u32; 6] {
     if counter_clockwise {
         [start, start + 1, start + 2, start + 1, start + 3, start + 2]
     } else {
         [start, start + 2, start + 1, start + 1, start + 2, start + 3]
     }
 }
+
+/// Same as [`quad_indices`], but the triangles share the diagonal from
+/// corner 0 to corner 3 instead of 1 to 2.
+fn flipped_quad_indices(start: u32, counter_clockwise: bool) -> [u32; 6] {
+    if counter_clockwise {
+        [start, start + 1, start + 3, start, start + 3, start + 2]
+    } else {
+        [start, start + 3, start + 1, start, start + 2, start + 3]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RIGHT_HANDED_Y_UP_CONFIG;
+
+    use ilattice::glam::Vec3;
+
+    #[test]
+    fn corner_values_choose_diagonal() {
+        let quad = UnorientedQuad {
+            minimum: [0; 3],
+            width: 1,
+            height: 1,
+        };
+        for face in RIGHT_HANDED_Y_UP_CONFIG.faces.iter() {
+            let positions = face.quad_mesh_positions(&quad, 1.0).map(Vec3::from);
+            let normal = face.signed_normal().as_vec3();
+
+            // Uniform values keep the usual triangulation.
+            assert_eq!(
+                face.quad_mesh_indices_with_corner_values(4, [3u8; 4]),
+                face.quad_mesh_indices(4)
+            );
+
+            for (values, diagonal) in [([0u8, 3, 3, 3], [1, 2]), ([3, 0, 3, 3], [0, 3])] {
+                let indices = face.quad_mesh_indices_with_corner_values(0, values);
+                for triangle in indices.chunks_exact(3) {
+                    // Both triangles contain the diagonal's endpoints.
+                    assert!(diagonal.iter().all(|i| triangle.contains(i)));
+                    // Winding is unchanged.
+                    let [a, b, c] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+                    assert!((b - a).cross(c - a).dot(normal) > 0.0);
+                }
+            }
+        }
+    }
+}
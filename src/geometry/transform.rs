@@ -0,0 +1,379 @@
+use crate::{Axis, OrientedBlockFace, QuadBuffer, QuadGroups, SignedAxis, UnorientedQuad};
+
+use ilattice::glam::{IVec3, UVec3};
+
+/// One of the 48 transforms that map the lattice axes onto each other: the 24 rotations of a cube by multiples of 90
+/// degrees, and each of those combined with a mirror.
+///
+/// This is for reusing the quads of a voxel array, like a prefab, that is placed in the world with a different
+/// orientation. [`AxisTransform::transform_quads`] gives the quads of the transformed array without meshing it again.
+///
+/// ```
+/// # use block_mesh::{Axis, AxisTransform, SignedAxis};
+/// let turn = AxisTransform::rotation(Axis::Y, 1);
+/// assert_eq!(turn.transform_axis(SignedAxis::PosX), SignedAxis::NegZ);
+/// assert_eq!(turn.then(&turn.inverse()), AxisTransform::IDENTITY);
+/// assert!(!turn.then(&AxisTransform::mirror(Axis::X)).is_rotation());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AxisTransform {
+    /// Where +X, +Y, and +Z are mapped to.
+    axes: [SignedAxis; 3],
+}
+
+impl AxisTransform {
+    pub const IDENTITY: Self = Self {
+        axes: [SignedAxis::PosX, SignedAxis::PosY, SignedAxis::PosZ],
+    };
+
+    /// The transform that maps +X, +Y, and +Z to `x`, `y`, and `z`, or `None` if they aren't on 3 different axes.
+    pub fn new(x: SignedAxis, y: SignedAxis, z: SignedAxis) -> Option<Self> {
+        let [ax, ay, az] = [x, y, z].map(|a| a.unsigned_axis());
+        (ax != ay && ay != az && az != ax).then_some(Self { axes: [x, y, z] })
+    }
+
+    /// A rotation by `quarter_turns` times 90 degrees around `axis`, counterclockwise when looking down `axis` from its
+    /// positive end, i.e. by the right-hand rule.
+    pub fn rotation(axis: Axis, quarter_turns: i32) -> Self {
+        // The other two axes, in the order that one quarter turn takes the first to the second.
+        let (a, b) = match axis {
+            Axis::X => (Axis::Y, Axis::Z),
+            Axis::Y => (Axis::Z, Axis::X),
+            Axis::Z => (Axis::X, Axis::Y),
+        };
+        let mut quarter_turn = Self::IDENTITY;
+        quarter_turn.axes[a.index()] = SignedAxis::new(1, b);
+        quarter_turn.axes[b.index()] = SignedAxis::new(-1, a);
+
+        let mut transform = Self::IDENTITY;
+        for _ in 0..quarter_turns.rem_euclid(4) {
+            transform = transform.then(&quarter_turn);
+        }
+        transform
+    }
+
+    /// The mirror that negates `axis`.
+    pub fn mirror(axis: Axis) -> Self {
+        let mut transform = Self::IDENTITY;
+        transform.axes[axis.index()] = SignedAxis::new(-1, axis);
+        transform
+    }
+
+    /// All 48 transforms, starting with the identity.
+    pub fn all() -> impl Iterator<Item = Self> {
+        const PERMUTATIONS: [[Axis; 3]; 6] = [
+            [Axis::X, Axis::Y, Axis::Z],
+            [Axis::X, Axis::Z, Axis::Y],
+            [Axis::Y, Axis::X, Axis::Z],
+            [Axis::Y, Axis::Z, Axis::X],
+            [Axis::Z, Axis::X, Axis::Y],
+            [Axis::Z, Axis::Y, Axis::X],
+        ];
+        PERMUTATIONS.into_iter().flat_map(|axes| {
+            (0..8).map(move |signs: i32| {
+                let mut i = 0;
+                Self {
+                    axes: axes.map(|axis| {
+                        let sign = if signs & (1 << i) == 0 { 1 } else { -1 };
+                        i += 1;
+                        SignedAxis::new(sign, axis)
+                    }),
+                }
+            })
+        })
+    }
+
+    /// The 24 transforms that are rotations, starting with the identity.
+    pub fn rotations() -> impl Iterator<Item = Self> {
+        Self::all().filter(|t| t.is_rotation())
+    }
+
+    /// True iff this is a rotation, and not a mirror.
+    pub fn is_rotation(&self) -> bool {
+        let [x, y, z] = self.axes.map(|a| a.get_unit_vector());
+        x.cross(y) == z
+    }
+
+    /// The transform that applies `self` and then `next`.
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            axes: self.axes.map(|a| next.transform_axis(a)),
+        }
+    }
+
+    /// The transform that undoes this one.
+    pub fn inverse(&self) -> Self {
+        let mut inverse = Self::IDENTITY;
+        for (from, to) in [Axis::X, Axis::Y, Axis::Z].into_iter().zip(self.axes) {
+            inverse.axes[to.unsigned_axis().index()] = SignedAxis::new(to.signum(), from);
+        }
+        inverse
+    }
+
+    #[inline]
+    pub fn transform_axis(&self, axis: SignedAxis) -> SignedAxis {
+        let image = self.axes[axis.unsigned_axis().index()];
+        if axis.signum() > 0 {
+            image
+        } else {
+            image.opposite()
+        }
+    }
+
+    #[inline]
+    pub fn transform_vector(&self, v: IVec3) -> IVec3 {
+        let [x, y, z] = self.axes.map(|a| a.get_unit_vector());
+        v.x * x + v.y * y + v.z * z
+    }
+
+    /// The shape of a voxel array of `shape` after the transform.
+    #[inline]
+    pub fn transform_shape(&self, shape: [u32; 3]) -> [u32; 3] {
+        let mut out = [0; 3];
+        for (&size, image) in shape.iter().zip(self.axes) {
+            out[image.unsigned_axis().index()] = size;
+        }
+        out
+    }
+
+    /// Maps the voxel at `p` in an array of `shape` to its place in the transformed array, whose shape is
+    /// [`AxisTransform::transform_shape`].
+    #[inline]
+    pub fn transform_voxel(&self, p: [u32; 3], shape: [u32; 3]) -> [u32; 3] {
+        let mut out = [0; 3];
+        for ((&c, &size), image) in p.iter().zip(shape.iter()).zip(self.axes) {
+            debug_assert!(c < size);
+            out[image.unsigned_axis().index()] = if image.signum() > 0 { c } else { size - 1 - c };
+        }
+        out
+    }
+
+    /// Transforms a quad in the group `group_index` of `faces`, from an array of `shape`, returning its new group and
+    /// quad.
+    ///
+    /// # Panics
+    ///
+    /// If `faces` doesn't have a face with the transformed normal, which can't happen for a
+    /// [valid](crate::QuadCoordinateConfig::validate) configuration.
+    pub fn transform_quad(
+        &self,
+        faces: &[OrientedBlockFace; 6],
+        group_index: usize,
+        quad: &UnorientedQuad,
+        shape: [u32; 3],
+    ) -> (usize, UnorientedQuad) {
+        let face = &faces[group_index];
+        let normal = self.transform_axis(face.normal_axis());
+        let new_index = faces
+            .iter()
+            .position(|f| f.normal_axis() == normal)
+            .unwrap_or_else(|| panic!("no face has the normal {normal:?}"));
+        let new_face = &faces[new_index];
+
+        // U and V either stay on the same axes of the new face or swap.
+        let u_image = self.axes[face.permutation().axes()[1].index()];
+        let (width, height) = if u_image.unsigned_axis() == new_face.permutation().axes()[1] {
+            (quad.width, quad.height)
+        } else {
+            (quad.height, quad.width)
+        };
+
+        let min = UVec3::from(quad.minimum);
+        let max = min + face.u * (quad.width - 1) + face.v * (quad.height - 1);
+        let [a, b] = [min, max].map(|p| UVec3::from(self.transform_voxel(p.to_array(), shape)));
+        let new_quad = UnorientedQuad {
+            minimum: a.min(b).to_array(),
+            width,
+            height,
+        };
+        (new_index, new_quad)
+    }
+
+    /// Transforms all of `quads`, which were generated with `faces` from an array of `shape`, into `output`. Any previous
+    /// contents of `output` are cleared.
+    ///
+    /// `quads` can come from any of the meshers, e.g. the [`UnitQuadBuffer`](crate::UnitQuadBuffer) of
+    /// [`visible_block_faces`](crate::visible_block_faces) or the [`QuadBuffer`] of
+    /// [`greedy_quads`](crate::greedy_quads).
+    ///
+    /// The result always covers exactly the block faces that meshing the transformed array would. For
+    /// `visible_block_faces`, it has the same quads as meshing the transformed array again, only in a different order.
+    ///
+    /// For the greedy meshers, it is **not** the same as meshing the transformed array again. The quads are still
+    /// maximal rectangles, but greedy meshing always grows quads along the U axis of a face first, so meshing the
+    /// transformed array can split the same faces into different quads. Mesh the transformed array again if the output
+    /// must match exactly.
+    pub fn transform_quads(
+        &self,
+        faces: &[OrientedBlockFace; 6],
+        quads: &impl QuadGroups,
+        shape: [u32; 3],
+        output: &mut QuadBuffer,
+    ) {
+        output.reset();
+        quads.for_each_quad(|group_index, quad| {
+            let (new_index, new_quad) = self.transform_quad(faces, group_index, &quad, shape);
+            output.groups[new_index].push(new_quad);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        greedy_quads, visible_block_faces, GreedyQuadsBuffer, UnitQuadBuffer, VoxVoxel,
+        RIGHT_HANDED_Y_UP_CONFIG,
+    };
+
+    use ndshape::{RuntimeShape, Shape};
+    use std::collections::BTreeSet;
+
+    const SHAPE: [u32; 3] = [5, 6, 7];
+
+    /// An asymmetric L shape of voxels, padded by 1.
+    fn prefab(transform: &AxisTransform) -> (RuntimeShape<u32, 3>, Vec<VoxVoxel>) {
+        let shape = RuntimeShape::<u32, 3>::new(transform.transform_shape(SHAPE));
+        let mut voxels = vec![VoxVoxel::EMPTY; shape.size() as usize];
+        for i in 0..RuntimeShape::<u32, 3>::new(SHAPE).size() {
+            let [x, y, z] = RuntimeShape::<u32, 3>::new(SHAPE).delinearize(i);
+            let inside = (1..4).contains(&x) && (1..5).contains(&y) && (1..6).contains(&z);
+            if inside && (y == 1 || z == 1) {
+                let p = transform.transform_voxel([x, y, z], SHAPE);
+                voxels[shape.linearize(p) as usize] = VoxVoxel(1 + x as u8);
+            }
+        }
+        (shape, voxels)
+    }
+
+    /// Every block face covered by `quads`, as its group and voxel.
+    fn unit_faces(
+        faces: &[OrientedBlockFace; 6],
+        quads: &impl QuadGroups,
+    ) -> BTreeSet<(usize, [u32; 3])> {
+        let mut set = BTreeSet::new();
+        quads.for_each_quad(|group_index, quad| {
+            let face = &faces[group_index];
+            for i in 0..quad.width {
+                for j in 0..quad.height {
+                    let p = UVec3::from(quad.minimum) + face.u * i + face.v * j;
+                    assert!(set.insert((group_index, p.to_array())));
+                }
+            }
+        });
+        set
+    }
+
+    fn unit_quads(shape: &RuntimeShape<u32, 3>, voxels: &[VoxVoxel]) -> UnitQuadBuffer {
+        let max = shape.as_array().map(|d| d - 1);
+        let mut buffer = UnitQuadBuffer::new();
+        visible_block_faces(
+            voxels,
+            shape,
+            [0; 3],
+            max,
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+        buffer
+    }
+
+    fn greedy(shape: &RuntimeShape<u32, 3>, voxels: &[VoxVoxel]) -> QuadBuffer {
+        let max = shape.as_array().map(|d| d - 1);
+        let mut buffer = GreedyQuadsBuffer::new(voxels.len());
+        greedy_quads(
+            voxels,
+            shape,
+            [0; 3],
+            max,
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+        buffer.quads
+    }
+
+    /// The quads of each group, in a canonical order.
+    fn sorted(quads: &QuadBuffer) -> Vec<Vec<UnorientedQuad>> {
+        quads
+            .groups
+            .iter()
+            .map(|group| {
+                let mut group = group.clone();
+                group.sort_by_key(|q| (q.minimum, q.width, q.height));
+                group
+            })
+            .collect()
+    }
+
+    #[test]
+    fn transforms_form_a_group() {
+        let all: Vec<_> = AxisTransform::all().collect();
+        assert_eq!(all.len(), 48);
+        assert_eq!(AxisTransform::rotations().count(), 24);
+        assert_eq!(all[0], AxisTransform::IDENTITY);
+        for a in all.iter() {
+            assert_eq!(a.then(&a.inverse()), AxisTransform::IDENTITY);
+            assert_eq!(
+                AxisTransform::new(a.axes[0], a.axes[1], a.axes[2]),
+                Some(*a)
+            );
+            for b in all.iter() {
+                let ab = a.then(b);
+                assert!(all.contains(&ab));
+                assert_eq!(ab.is_rotation(), a.is_rotation() == b.is_rotation());
+                let v = IVec3::new(1, 2, 3);
+                assert_eq!(
+                    ab.transform_vector(v),
+                    b.transform_vector(a.transform_vector(v))
+                );
+            }
+        }
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            assert_eq!(AxisTransform::rotation(axis, 4), AxisTransform::IDENTITY);
+            assert_eq!(
+                AxisTransform::rotation(axis, -1),
+                AxisTransform::rotation(axis, 3)
+            );
+        }
+        let turn = AxisTransform::rotation(Axis::Z, 1);
+        assert_eq!(turn.transform_vector(IVec3::X), IVec3::Y);
+        assert_eq!(
+            AxisTransform::new(SignedAxis::PosX, SignedAxis::NegX, SignedAxis::PosZ),
+            None
+        );
+    }
+
+    #[test]
+    fn transformed_quads_match_meshing_transformed_voxels() {
+        let faces = &RIGHT_HANDED_Y_UP_CONFIG.faces;
+        let (shape, voxels) = prefab(&AxisTransform::IDENTITY);
+        let quads = unit_quads(&shape, &voxels);
+        let greedy_quads = greedy(&shape, &voxels);
+
+        let mut transformed = QuadBuffer::new();
+        let mut num_greedy_mismatches = 0;
+        for transform in AxisTransform::all() {
+            let (new_shape, new_voxels) = prefab(&transform);
+
+            // Unit quads are the same as meshing the transformed array again.
+            let mut expected = QuadBuffer::new();
+            unit_quads(&new_shape, &new_voxels).for_each_quad(|group_index, quad| {
+                expected.groups[group_index].push(quad);
+            });
+            transform.transform_quads(faces, &quads, SHAPE, &mut transformed);
+            assert_eq!(sorted(&transformed), sorted(&expected));
+
+            // Greedy quads cover the same faces, but aren't always the quads that meshing again would give.
+            transform.transform_quads(faces, &greedy_quads, SHAPE, &mut transformed);
+            assert_eq!(transformed.num_quads(), greedy_quads.num_quads());
+            assert_eq!(
+                unit_faces(faces, &transformed),
+                unit_faces(faces, &expected)
+            );
+            if sorted(&transformed) != sorted(&greedy(&new_shape, &new_voxels)) {
+                num_greedy_mismatches += 1;
+            }
+        }
+        assert!(num_greedy_mismatches > 0);
+    }
+}
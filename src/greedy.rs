@@ -1,8 +1,15 @@
 mod merge_strategy;
+#[cfg(feature = "rayon")]
+mod parallel;
 
 pub use merge_strategy::*;
+#[cfg(feature = "rayon")]
+pub use parallel::*;
 
-use crate::{bounds::assert_in_bounds, OrientedBlockFace, QuadBuffer, UnorientedQuad, Voxel, VoxelVisibility};
+use crate::{
+    access::ShapedSlice, binary_greedy::OccupancyRows, bounds::assert_in_bounds, face_is_visible,
+    OrientedBlockFace, QuadBuffer, SignedAxis, UnorientedQuad, Voxel, VoxelAccess, VoxelVisibility,
+};
 
 use ilattice::glam::UVec3;
 use ilattice::prelude::Extent;
@@ -17,31 +24,63 @@ pub trait MergeVoxel: Voxel {
     /// in the same quad. Often this is some material identifier so that the same texture can be used for a full quad.
     fn merge_value(&self) -> Self::MergeValue;
 
+    /// The merge value of this voxel's face that points towards `face`. Quads are only merged where this matches, so a grass
+    /// block can use one material on its top face and another on its sides, all as the same voxel type.
+    ///
+    /// Defaults to [`merge_value`](Self::merge_value) on every face.
+    fn merge_value_for_face(&self, face: SignedAxis) -> Self::MergeValue {
+        let _ = face;
+        self.merge_value()
+    }
+
     fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour;
 }
 
 /// Contains the output from the [`greedy_quads`] algorithm. The quads can be used to generate a mesh. See the methods on
 /// [`OrientedBlockFace`] and [`UnorientedQuad`] for details.
 ///
-/// This buffer can be reused between multiple calls of [`greedy_quads`] in order to avoid reallocations.
+/// This buffer can be reused between multiple calls of [`greedy_quads`] or
+/// [`binary_greedy_quads`](crate::binary_greedy_quads) in order to avoid reallocations.
 pub struct GreedyQuadsBuffer {
     pub quads: QuadBuffer,
 
+    /// The ambient occlusion values at the 4 corners of each quad, in the same order as
+    /// [`OrientedBlockFace::quad_corners`]. `ao[i][j]` belongs to `quads.groups[i][j]`.
+    ///
+    /// This is only filled by merge strategies that compute ambient occlusion, like [`AoVoxelMerger`]. Otherwise it's empty.
+    /// Triangulate quads with [`OrientedBlockFace::quad_mesh_indices_with_corner_values`] to interpolate these evenly.
+    pub ao: [Vec<[u8; 4]>; 6],
+
     // A single array is used for the visited mask because it allows us to index by the same strides as the voxels array. It
     // also only requires a single allocation.
-    visited: Vec<bool>,
+    pub(crate) visited: Vec<bool>,
+
+    // Scratch space for `binary_greedy_quads`.
+    pub(crate) occupancy: OccupancyRows,
+
+    // One visited mask for each job of `par_greedy_quads`, kept so that they're only allocated once.
+    #[cfg(feature = "rayon")]
+    pub(crate) par_visited: Vec<Vec<bool>>,
 }
 
 impl GreedyQuadsBuffer {
     pub fn new(size: usize) -> Self {
+        const EMPTY: Vec<[u8; 4]> = Vec::new();
         Self {
             quads: QuadBuffer::new(),
+            ao: [EMPTY; 6],
             visited: vec![false; size],
+            occupancy: OccupancyRows::default(),
+            #[cfg(feature = "rayon")]
+            par_visited: Vec::new(),
         }
     }
 
     pub fn reset(&mut self, size: usize) {
         self.quads.reset();
+        for group in self.ao.iter_mut() {
+            group.clear();
+        }
 
         if size != self.visited.len() {
             self.visited = vec![false; size];
@@ -94,39 +133,164 @@ pub fn greedy_quads_with_merge_strategy<T, S, Merger>(
 {
     assert_in_bounds(voxels, voxels_shape, min, max);
 
-    let min = UVec3::from(min).as_ivec3();
-    let max = UVec3::from(max).as_ivec3();
-    let extent = Extent::from_min_and_max(min, max);
-
     output.reset(voxels.len());
     let GreedyQuadsBuffer {
         visited,
+        ao,
         quads: QuadBuffer { groups },
+        ..
     } = output;
 
-    let interior = extent.padded(-1); // Avoid accessing out of bounds with a 3x3x3 kernel.
-    let interior =
-        Extent::from_min_and_shape(interior.minimum.as_uvec3(), interior.shape.as_uvec3());
+    let interior = interior_extent(min, max);
+    let voxels = ShapedSlice {
+        voxels,
+        shape: voxels_shape,
+    };
 
-    for (group, face) in groups.iter_mut().zip(faces.iter()) {
-        greedy_quads_for_face::<_, _, Merger>(voxels, voxels_shape, interior, face, visited, group);
+    for ((group, ao_group), face) in groups.iter_mut().zip(ao.iter_mut()).zip(faces.iter()) {
+        greedy_quads_for_face::<_, Merger>(&voxels, interior, face, visited, group, ao_group);
     }
 }
 
-fn greedy_quads_for_face<T, S, Merger>(
+/// Updates the output of a previous [`greedy_quads`] call after the voxels in `dirty` have changed.
+///
+/// Only the slices of each face group whose faces could be affected by `dirty` are removed and meshed again, so this is much
+/// cheaper than meshing the whole extent after a small edit. The result is identical to calling [`greedy_quads`] again,
+/// including the order of quads.
+///
+/// `output` must contain the result of meshing the same `voxels_shape`, `min`, `max`, and `faces` before the edit, and all
+/// voxels that changed since must be in `dirty`.
+pub fn greedy_quads_dirty<T, S>(
     voxels: &[T],
     voxels_shape: &S,
-    interior: Extent<UVec3>,
-    face: &OrientedBlockFace,
-    visited: &mut [bool],
-    quads: &mut Vec<UnorientedQuad>,
+    min: [u32; 3],
+    max: [u32; 3],
+    dirty: Extent<UVec3>,
+    faces: &[OrientedBlockFace; 6],
+    output: &mut GreedyQuadsBuffer,
+) where
+    T: MergeVoxel,
+    S: Shape<3, Coord = u32>,
+{
+    greedy_quads_dirty_with_merge_strategy::<_, _, VoxelMerger<T>>(
+        voxels,
+        voxels_shape,
+        min,
+        max,
+        dirty,
+        faces,
+        output,
+    )
+}
+
+/// Same as [`greedy_quads_dirty`], but for the output of [`greedy_quads_with_merge_strategy`] with the same `Merger`.
+pub fn greedy_quads_dirty_with_merge_strategy<T, S, Merger>(
+    voxels: &[T],
+    voxels_shape: &S,
+    min: [u32; 3],
+    max: [u32; 3],
+    dirty: Extent<UVec3>,
+    faces: &[OrientedBlockFace; 6],
+    output: &mut GreedyQuadsBuffer,
 ) where
     T: Voxel,
     S: Shape<3, Coord = u32>,
     Merger: MergeStrategy<Voxel = T>,
 {
-    visited.fill(false);
+    assert_in_bounds(voxels, voxels_shape, min, max);
+
+    if dirty.shape.cmpeq(UVec3::ZERO).any() {
+        return;
+    }
+    if output.visited.len() != voxels.len() {
+        output.visited = vec![false; voxels.len()];
+    }
+    let GreedyQuadsBuffer {
+        visited,
+        ao,
+        quads: QuadBuffer { groups },
+        ..
+    } = output;
+
+    let interior = interior_extent(min, max);
+    let voxels = ShapedSlice {
+        voxels,
+        shape: voxels_shape,
+    };
+    let interior_min = interior.minimum.to_array();
+    let interior_max = interior.max().to_array();
+    let dirty_min = dirty.minimum.to_array();
+    let dirty_max = dirty.max().to_array();
+
+    let mut new_quads = Vec::new();
+    let mut new_ao = Vec::new();
+    for ((group, ao_group), face) in groups.iter_mut().zip(ao.iter_mut()).zip(faces.iter()) {
+        let i_n = face.permutation.axes()[0].index();
+
+        // A face depends on its own voxel and the voxel it faces.
+        let (first_slice, last_slice) = if face.n_sign > 0 {
+            (dirty_min[i_n].saturating_sub(1), dirty_max[i_n])
+        } else {
+            (dirty_min[i_n], dirty_max[i_n].saturating_add(1))
+        };
+        let first_slice = first_slice.max(interior_min[i_n]);
+        let last_slice = last_slice.min(interior_max[i_n]);
+        if first_slice > last_slice {
+            continue;
+        }
+
+        let mut slices_min = interior.minimum;
+        slices_min[i_n] = first_slice;
+        let mut slices_shape = interior.shape;
+        slices_shape[i_n] = last_slice - first_slice + 1;
+        let slices = Extent::from_min_and_shape(slices_min, slices_shape);
+
+        new_quads.clear();
+        new_ao.clear();
+        greedy_quads_for_face::<_, Merger>(
+            &voxels,
+            slices,
+            face,
+            visited,
+            &mut new_quads,
+            &mut new_ao,
+        );
+
+        // Quads are sorted by slice, so the quads being replaced are contiguous.
+        let start = group.partition_point(|q| q.minimum[i_n] < first_slice);
+        let end = group.partition_point(|q| q.minimum[i_n] <= last_slice);
+        group.splice(start..end, new_quads.drain(..));
+        if Merger::COMPUTES_AO {
+            ao_group.splice(start..end, new_ao.drain(..));
+        }
+    }
+}
+
+/// The extent of voxels that get meshed when meshing `[min, max]`.
+pub(crate) fn interior_extent(min: [u32; 3], max: [u32; 3]) -> Extent<UVec3> {
+    let min = UVec3::from(min).as_ivec3();
+    let max = UVec3::from(max).as_ivec3();
+    let extent = Extent::from_min_and_max(min, max);
+
+    let interior = extent.padded(-1); // Avoid accessing out of bounds with a 3x3x3 kernel.
+    Extent::from_min_and_shape(interior.minimum.as_uvec3(), interior.shape.as_uvec3())
+}
 
+/// Meshes every slice of `slices` for a single face, pushing quads in order of increasing slice. `slices` must be a subset
+/// of the interior of the meshed extent.
+pub(crate) fn greedy_quads_for_face<A, Merger>(
+    voxels: &A,
+    slices: Extent<UVec3>,
+    face: &OrientedBlockFace,
+    visited: &mut [bool],
+    quads: &mut Vec<UnorientedQuad>,
+    quads_ao: &mut Vec<[u8; 4]>,
+) where
+    A: VoxelAccess,
+    A::Voxel: Voxel,
+    Merger: MergeStrategy<Voxel = A::Voxel>,
+{
+    let voxels_shape = voxels.shape();
     let OrientedBlockFace {
         n_sign,
         permutation,
@@ -141,13 +305,13 @@ fn greedy_quads_for_face<T, S, Merger>(
     let i_u = u_axis.index();
     let i_v = v_axis.index();
 
-    let interior_shape = interior.shape.to_array();
-    let num_slices = interior_shape[i_n];
+    let slices_shape = slices.shape.to_array();
+    let num_slices = slices_shape[i_n];
     let mut slice_shape = [0; 3];
     slice_shape[i_n] = 1;
-    slice_shape[i_u] = interior_shape[i_u];
-    slice_shape[i_v] = interior_shape[i_v];
-    let mut slice_extent = Extent::from_min_and_shape(interior.minimum, UVec3::from(slice_shape));
+    slice_shape[i_u] = slices_shape[i_u];
+    slice_shape[i_v] = slices_shape[i_v];
+    let mut slice_extent = Extent::from_min_and_shape(slices.minimum, UVec3::from(slice_shape));
 
     let n_stride = voxels_shape.linearize(n.to_array());
     let u_stride = voxels_shape.linearize(u.to_array());
@@ -162,9 +326,19 @@ fn greedy_quads_for_face<T, S, Merger>(
         } else {
             0u32.wrapping_sub(n_stride)
         },
+        normal: face.normal_axis(),
     };
 
     for _ in 0..num_slices {
+        // Quads never cross slices, so only this slice needs to be cleared.
+        fill3(
+            slice_shape,
+            false,
+            visited,
+            voxels_shape,
+            slice_extent.minimum.to_array(),
+        );
+
         let slice_ub = slice_extent.least_upper_bound().to_array();
         let u_ub = slice_ub[i_u];
         let v_ub = slice_ub[i_v];
@@ -172,12 +346,12 @@ fn greedy_quads_for_face<T, S, Merger>(
         for quad_min in slice_extent.iter3() {
             let quad_min_array = quad_min.to_array();
             let quad_min_index = voxels_shape.linearize(quad_min_array);
-            let quad_min_voxel = unsafe { voxels.get_unchecked(quad_min_index as usize) };
+            let quad_min_voxel = unsafe { voxels.get_unchecked(quad_min_index) };
             if unsafe {
                 !face_needs_mesh(
                     quad_min_voxel,
                     quad_min_index,
-                    face_strides.visibility_offset,
+                    &face_strides,
                     voxels,
                     visited,
                 )
@@ -217,6 +391,9 @@ fn greedy_quads_for_face<T, S, Merger>(
                 width: quad_width,
                 height: quad_height,
             });
+            if Merger::COMPUTES_AO {
+                quads_ao.push(unsafe { Merger::quad_ao(quad_min_index, &face_strides, voxels) });
+            }
         }
 
         // Move to the next slice.
@@ -225,43 +402,39 @@ fn greedy_quads_for_face<T, S, Merger>(
 }
 
 /// Returns true iff the given `voxel` face needs to be meshed. This means that we haven't already meshed it, it is non-empty,
-/// and it's visible (not completely occluded by an adjacent voxel).
-pub(crate) unsafe fn face_needs_mesh<T>(
-    voxel: &T,
+/// and it's visible (not completely occluded by an adjacent voxel, or in the same translucency group as it).
+pub(crate) unsafe fn face_needs_mesh<A>(
+    voxel: &A::Voxel,
     voxel_stride: u32,
-    visibility_offset: u32,
-    voxels: &[T],
+    face_strides: &FaceStrides,
+    voxels: &A,
     visited: &[bool],
 ) -> bool
 where
-    T: Voxel,
+    A: VoxelAccess,
+    A::Voxel: Voxel,
 {
     if voxel.get_visibility() == VoxelVisibility::Empty || visited[voxel_stride as usize] {
         return false;
     }
 
     let adjacent_voxel =
-        voxels.get_unchecked(voxel_stride.wrapping_add(visibility_offset) as usize);
-
-    // TODO: If the face lies between two transparent voxels, we choose not to mesh it. We might need to extend the IsOpaque
-    // trait with different levels of transparency to support this.
-    match adjacent_voxel.get_visibility() {
-        VoxelVisibility::Empty => true,
-        VoxelVisibility::Translucent => voxel.get_visibility() == VoxelVisibility::Opaque,
-        VoxelVisibility::Opaque => false,
-    }
+        voxels.get_unchecked(voxel_stride.wrapping_add(face_strides.visibility_offset));
+
+    face_is_visible(voxel, adjacent_voxel, face_strides.normal)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::{bool_voxels, minimums, BoolVoxel, Material, XorShift};
     use crate::RIGHT_HANDED_Y_UP_CONFIG;
     use ndshape::{ConstShape, ConstShape3u32};
 
     #[test]
     #[should_panic]
     fn panics_with_max_out_of_bounds_access() {
-        let samples = [EMPTY; SampleShape::SIZE as usize];
+        let samples = [BoolVoxel::EMPTY; SampleShape::SIZE as usize];
         let mut buffer = GreedyQuadsBuffer::new(samples.len());
         greedy_quads(
             &samples,
@@ -276,7 +449,7 @@ mod tests {
     #[test]
     #[should_panic]
     fn panics_with_min_out_of_bounds_access() {
-        let samples = [EMPTY; SampleShape::SIZE as usize];
+        let samples = [BoolVoxel::EMPTY; SampleShape::SIZE as usize];
         let mut buffer = GreedyQuadsBuffer::new(samples.len());
         greedy_quads(
             &samples,
@@ -288,34 +461,222 @@ mod tests {
         );
     }
 
-    type SampleShape = ConstShape3u32<34, 34, 34>;
+    #[test]
+    fn ao_merger_merges_unoccluded_faces() {
+        let samples = floor_samples();
+        let mut buffer = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads_with_merge_strategy::<_, _, AoVoxelMerger<BoolVoxel>>(
+            &samples,
+            &AoShape {},
+            [0; 3],
+            [5; 3],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+
+        // The +Y face group.
+        assert_eq!(buffer.quads.groups[4].len(), 1);
+        assert_eq!(buffer.ao[4], vec![[3; 4]]);
+        for (quads, ao) in buffer.quads.groups.iter().zip(buffer.ao.iter()) {
+            assert_eq!(quads.len(), ao.len());
+        }
+    }
 
-    /// Basic voxel type with one byte of texture layers
-    #[derive(Default, Clone, Copy, Eq, PartialEq)]
-    struct BoolVoxel(bool);
+    #[test]
+    fn ao_merger_splits_occluded_faces() {
+        let mut samples = floor_samples();
+        samples[AoShape {}.linearize([2, 2, 2]) as usize] = BoolVoxel(true);
+
+        let mut plain_buffer = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads(
+            &samples,
+            &AoShape {},
+            [0; 3],
+            [5; 3],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut plain_buffer,
+        );
+        let mut ao_buffer = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads_with_merge_strategy::<_, _, AoVoxelMerger<BoolVoxel>>(
+            &samples,
+            &AoShape {},
+            [0; 3],
+            [5; 3],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut ao_buffer,
+        );
 
-    const EMPTY: BoolVoxel = BoolVoxel(false);
+        let area =
+            |quads: &[UnorientedQuad]| -> u32 { quads.iter().map(|q| q.width * q.height).sum() };
+        let plain_top = &plain_buffer.quads.groups[4];
+        let ao_top = &ao_buffer.quads.groups[4];
+        assert_eq!(area(plain_top), area(ao_top));
+        assert!(ao_top.len() > plain_top.len());
+
+        // The floor face at +X of the block is occluded on its -X corners, which are -V for the +Y face.
+        let i = ao_top
+            .iter()
+            .position(|q| q.minimum == [3, 1, 2] && q.height == 1)
+            .unwrap();
+        assert_eq!(ao_buffer.ao[4][i], [2, 2, 3, 3]);
+        // The block's own top face is unoccluded.
+        let i = ao_top.iter().position(|q| q.minimum == [2, 2, 2]).unwrap();
+        assert_eq!(ao_buffer.ao[4][i], [3; 4]);
+    }
 
-    impl Voxel for BoolVoxel {
-        fn get_visibility(&self) -> VoxelVisibility {
-            if *self == EMPTY {
-                VoxelVisibility::Empty
-            } else {
-                VoxelVisibility::Opaque
-            }
-        }
+    #[test]
+    fn vertex_ao_values() {
+        assert_eq!(vertex_ao(false, false, false), 3);
+        assert_eq!(vertex_ao(false, false, true), 2);
+        assert_eq!(vertex_ao(true, false, true), 1);
+        assert_eq!(vertex_ao(true, true, false), 0);
+    }
+
+    #[test]
+    fn meshes_faces_between_translucent_groups() {
+        let mut samples = [Material::Air; RowShape::SIZE as usize];
+        samples[RowShape {}.linearize([1, 1, 1]) as usize] = Material::Glass;
+        samples[RowShape {}.linearize([2, 1, 1]) as usize] = Material::Water;
+        samples[RowShape {}.linearize([3, 1, 1]) as usize] = Material::Water;
+
+        let mut buffer = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads(
+            &samples,
+            &RowShape {},
+            [0; 3],
+            [4, 2, 2],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+
+        // -X faces.
+        assert_eq!(minimums(&buffer.quads.groups[0]), [[1, 1, 1], [2, 1, 1]]);
+        // +X faces.
+        assert_eq!(minimums(&buffer.quads.groups[3]), [[1, 1, 1], [3, 1, 1]]);
+        // The water's -Y face is merged along X, but not with the glass.
+        assert_eq!(minimums(&buffer.quads.groups[1]), [[1, 1, 1], [2, 1, 1]]);
     }
 
-    impl MergeVoxel for BoolVoxel {
-        type MergeValue = Self;
-        type MergeValueFacingNeighbour = bool;
+    #[test]
+    fn uses_face_visibility() {
+        // A slab only occludes its neighbour below it, so the stone's +X face is visible.
+        let mut samples = [Material::Air; RowShape::SIZE as usize];
+        samples[RowShape {}.linearize([1, 1, 1]) as usize] = Material::Stone;
+        samples[RowShape {}.linearize([2, 1, 1]) as usize] = Material::Slab;
+
+        let mut buffer = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads(
+            &samples,
+            &RowShape {},
+            [0; 3],
+            [4, 2, 2],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+
+        // -X faces.
+        assert_eq!(minimums(&buffer.quads.groups[0]), [[1, 1, 1]]);
+        // +X faces.
+        assert_eq!(minimums(&buffer.quads.groups[3]), [[1, 1, 1], [2, 1, 1]]);
+    }
 
-        fn merge_value(&self) -> Self::MergeValue {
-            *self
+    #[test]
+    fn merges_face_specific_values() {
+        fn check<Merger: MergeStrategy<Voxel = Material>>() {
+            // Grass has dirt sides, so only its top face differs from the dirt next to it.
+            let mut samples = [Material::Air; RowShape::SIZE as usize];
+            samples[RowShape {}.linearize([1, 1, 1]) as usize] = Material::Grass;
+            samples[RowShape {}.linearize([2, 1, 1]) as usize] = Material::Dirt;
+
+            let mut buffer = GreedyQuadsBuffer::new(samples.len());
+            greedy_quads_with_merge_strategy::<_, _, Merger>(
+                &samples,
+                &RowShape {},
+                [0; 3],
+                [4, 2, 2],
+                &RIGHT_HANDED_Y_UP_CONFIG.faces,
+                &mut buffer,
+            );
+
+            let group_lens = buffer.quads.groups.each_ref().map(|g| g.len());
+            // Faces are in the order -X, -Y, -Z, +X, +Y, +Z.
+            assert_eq!(group_lens, [1, 1, 1, 1, 2, 1]);
         }
+        check::<VoxelMerger<Material>>();
+        check::<AoVoxelMerger<Material>>();
+    }
+
+    #[test]
+    fn dirty_remesh_matches_full_remesh() {
+        fn check<Merger: MergeStrategy<Voxel = Material>>() {
+            let mut samples = [Material::Air; NoiseShape::SIZE as usize];
+            let mut rng = XorShift(0x9e37_79b9);
+            for sample in samples.iter_mut() {
+                *sample = rng.choose(&[Material::Air, Material::Stone, Material::Glass]);
+            }
 
-        fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour {
-            true
+            let mut buffer = GreedyQuadsBuffer::new(samples.len());
+            greedy_quads_with_merge_strategy::<_, _, Merger>(
+                &samples,
+                &NoiseShape {},
+                [0; 3],
+                [11; 3],
+                &RIGHT_HANDED_Y_UP_CONFIG.faces,
+                &mut buffer,
+            );
+
+            // Single voxels in the interior and on the padding, a larger region, and nothing.
+            let edits = [
+                Extent::from_min_and_shape(UVec3::new(5, 6, 7), UVec3::ONE),
+                Extent::from_min_and_shape(UVec3::new(0, 3, 4), UVec3::ONE),
+                Extent::from_min_and_shape(UVec3::new(1, 8, 2), UVec3::new(4, 3, 5)),
+                Extent::from_min_and_shape(UVec3::ZERO, UVec3::new(3, 0, 3)),
+            ];
+            for dirty in edits {
+                for p in dirty.iter3() {
+                    let i = NoiseShape {}.linearize(p.to_array()) as usize;
+                    samples[i] = rng.choose(&[Material::Air, Material::Stone, Material::Water]);
+                }
+                greedy_quads_dirty_with_merge_strategy::<_, _, Merger>(
+                    &samples,
+                    &NoiseShape {},
+                    [0; 3],
+                    [11; 3],
+                    dirty,
+                    &RIGHT_HANDED_Y_UP_CONFIG.faces,
+                    &mut buffer,
+                );
+
+                let mut expected = GreedyQuadsBuffer::new(samples.len());
+                greedy_quads_with_merge_strategy::<_, _, Merger>(
+                    &samples,
+                    &NoiseShape {},
+                    [0; 3],
+                    [11; 3],
+                    &RIGHT_HANDED_Y_UP_CONFIG.faces,
+                    &mut expected,
+                );
+                assert_eq!(buffer.quads.groups, expected.quads.groups);
+                assert_eq!(buffer.ao, expected.ao);
+            }
         }
+
+        check::<VoxelMerger<Material>>();
+        check::<AoVoxelMerger<Material>>();
+    }
+
+    type SampleShape = ConstShape3u32<34, 34, 34>;
+
+    type NoiseShape = ConstShape3u32<12, 12, 12>;
+
+    type RowShape = ConstShape3u32<5, 3, 3>;
+
+    type AoShape = ConstShape3u32<6, 6, 6>;
+
+    /// A solid floor one voxel thick at `y = 1`.
+    fn floor_samples() -> Vec<BoolVoxel> {
+        bool_voxels(&AoShape {}, |[x, y, z]| {
+            y == 1 && (1..5).contains(&x) && (1..5).contains(&z)
+        })
     }
 }
@@ -1,10 +1,8 @@
 use crate::greedy::face_needs_mesh;
-use crate::Voxel;
+use crate::{SignedAxis, Voxel, VoxelAccess, VoxelVisibility};
 
 use super::MergeVoxel;
 
-// TODO: implement a MergeStrategy for voxels with an ambient occlusion value at each vertex
-
 /// A strategy for merging cube faces into quads.
 pub trait MergeStrategy {
     type Voxel;
@@ -19,25 +17,47 @@ pub trait MergeStrategy {
     ///
     /// `face_strides`: Strides to help with indexing in the necessary directions for this cube face.
     ///
-    /// `voxels`: The entire array of voxel data.
+    /// `voxels`: All of the voxel data, indexed by the same strides as `face_strides`.
     ///
-    /// `visited`: The bitmask of which voxels have already been meshed. A quad's extent will be marked as visited (`true`)
-    ///            after `find_quad` returns.
+    /// `visited`: The bitmask of which voxels have already been meshed, with the same indices as `voxels`. A quad's
+    ///            extent will be marked as visited (`true`) after `find_quad` returns.
     ///
     /// # Safety
     ///
     /// Some implementations may use unchecked indexing of `voxels` for performance. If this trait is not invoked with correct
     /// arguments, access out of bounds may cause undefined behavior.
-    unsafe fn find_quad(
+    unsafe fn find_quad<A>(
         min_index: u32,
         max_width: u32,
         max_height: u32,
         face_strides: &FaceStrides,
-        voxels: &[Self::Voxel],
+        voxels: &A,
         visited: &[bool],
     ) -> (u32, u32)
     where
+        A: VoxelAccess<Voxel = Self::Voxel>,
         Self::Voxel: Voxel;
+
+    /// Whether this strategy computes ambient occlusion. If it does, [`MergeStrategy::quad_ao`] is called for every
+    /// quad, and [`GreedyQuadsBuffer::ao`](crate::GreedyQuadsBuffer::ao) is parallel to the quad groups. Otherwise it's
+    /// left empty.
+    const COMPUTES_AO: bool = false;
+
+    /// Return the ambient occlusion values at the 4 corners of the quad whose minimum voxel is at `min_index`, in the same
+    /// order as [`OrientedBlockFace::quad_corners`](crate::OrientedBlockFace::quad_corners).
+    ///
+    /// This is only called if [`MergeStrategy::COMPUTES_AO`] is true. By default, no corner is occluded.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`MergeStrategy::find_quad`].
+    unsafe fn quad_ao<A>(_min_index: u32, _face_strides: &FaceStrides, _voxels: &A) -> [u8; 4]
+    where
+        A: VoxelAccess<Voxel = Self::Voxel>,
+        Self::Voxel: Voxel,
+    {
+        [3; 4]
+    }
 }
 
 pub struct FaceStrides {
@@ -45,6 +65,8 @@ pub struct FaceStrides {
     pub u_stride: u32,
     pub v_stride: u32,
     pub visibility_offset: u32,
+    /// The outward normal of the face being meshed.
+    pub normal: SignedAxis,
 }
 
 pub struct VoxelMerger<T> {
@@ -57,18 +79,23 @@ where
 {
     type Voxel = T;
 
-    unsafe fn find_quad(
+    unsafe fn find_quad<A>(
         min_index: u32,
         max_width: u32,
         max_height: u32,
         face_strides: &FaceStrides,
-        voxels: &[T],
+        voxels: &A,
         visited: &[bool],
-    ) -> (u32, u32) {
+    ) -> (u32, u32)
+    where
+        A: VoxelAccess<Voxel = T>,
+    {
         // Greedily search for the biggest visible quad where all merge values are the same.
-        let quad_value = voxels.get_unchecked(min_index as usize).merge_value();
+        let quad_value = voxels
+            .get_unchecked(min_index)
+            .merge_value_for_face(face_strides.normal);
         let quad_neighbour_value = voxels
-            .get_unchecked(min_index.wrapping_add(face_strides.visibility_offset) as usize)
+            .get_unchecked(min_index.wrapping_add(face_strides.visibility_offset))
             .merge_value_facing_neighbour();
 
         // Start by finding the widest quad in the U direction.
@@ -78,7 +105,7 @@ where
             visited,
             &quad_value,
             &quad_neighbour_value,
-            face_strides.visibility_offset,
+            face_strides,
             row_start_stride,
             face_strides.u_stride,
             max_width,
@@ -93,7 +120,7 @@ where
                 visited,
                 &quad_value,
                 &quad_neighbour_value,
-                face_strides.visibility_offset,
+                face_strides,
                 row_start_stride,
                 face_strides.u_stride,
                 quad_width,
@@ -110,31 +137,35 @@ where
 }
 
 impl<T> VoxelMerger<T> {
-    unsafe fn get_row_width(
-        voxels: &[T],
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn get_row_width<A>(
+        voxels: &A,
         visited: &[bool],
         quad_merge_voxel_value: &T::MergeValue,
         quad_merge_voxel_value_facing_neighbour: &T::MergeValueFacingNeighbour,
-        visibility_offset: u32,
+        face_strides: &FaceStrides,
         start_stride: u32,
         delta_stride: u32,
         max_width: u32,
     ) -> u32
     where
+        A: VoxelAccess<Voxel = T>,
         T: MergeVoxel,
     {
         let mut quad_width = 0;
         let mut row_stride = start_stride;
         while quad_width < max_width {
-            let voxel = voxels.get_unchecked(row_stride as usize);
+            let voxel = voxels.get_unchecked(row_stride);
             let neighbour =
-                voxels.get_unchecked(row_stride.wrapping_add(visibility_offset) as usize);
+                voxels.get_unchecked(row_stride.wrapping_add(face_strides.visibility_offset));
 
-            if !face_needs_mesh(voxel, row_stride, visibility_offset, voxels, visited) {
+            if !face_needs_mesh(voxel, row_stride, face_strides, voxels, visited) {
                 break;
             }
 
-            if !voxel.merge_value().eq(quad_merge_voxel_value)
+            if !voxel
+                .merge_value_for_face(face_strides.normal)
+                .eq(quad_merge_voxel_value)
                 || !neighbour
                     .merge_value_facing_neighbour()
                     .eq(quad_merge_voxel_value_facing_neighbour)
@@ -150,3 +181,132 @@ impl<T> VoxelMerger<T> {
         quad_width
     }
 }
+
+/// A [`MergeStrategy`] for [`MergeVoxel`]s that also computes the classic 0-3 ambient occlusion value at each corner of a
+/// face.
+///
+/// Faces are only merged if their merge values match and all four of their corner AO values are equal, so the AO of a quad
+/// is the AO of any one of its faces. The values are written to [`GreedyQuadsBuffer::ao`](crate::GreedyQuadsBuffer::ao).
+///
+/// A corner's AO is computed from the two side voxels and the corner voxel adjacent to it in the layer of voxels that the
+/// face is looking into. Only [`VoxelVisibility::Opaque`] voxels occlude. A value of 3 means no occlusion, and 0 means fully
+/// occluded.
+pub struct AoVoxelMerger<T> {
+    marker: std::marker::PhantomData<T>,
+}
+
+impl<T> MergeStrategy for AoVoxelMerger<T>
+where
+    T: MergeVoxel,
+{
+    type Voxel = T;
+
+    unsafe fn find_quad<A>(
+        min_index: u32,
+        max_width: u32,
+        max_height: u32,
+        face_strides: &FaceStrides,
+        voxels: &A,
+        visited: &[bool],
+    ) -> (u32, u32)
+    where
+        A: VoxelAccess<Voxel = T>,
+    {
+        let quad_value = voxels
+            .get_unchecked(min_index)
+            .merge_value_for_face(face_strides.normal);
+        let quad_neighbour_value = voxels
+            .get_unchecked(min_index.wrapping_add(face_strides.visibility_offset))
+            .merge_value_facing_neighbour();
+        let quad_ao = face_ao(min_index, face_strides, voxels);
+
+        let can_merge = |index: u32| {
+            let voxel = voxels.get_unchecked(index);
+            let neighbour =
+                voxels.get_unchecked(index.wrapping_add(face_strides.visibility_offset));
+
+            face_needs_mesh(voxel, index, face_strides, voxels, visited)
+                && voxel
+                    .merge_value_for_face(face_strides.normal)
+                    .eq(&quad_value)
+                && neighbour
+                    .merge_value_facing_neighbour()
+                    .eq(&quad_neighbour_value)
+                && face_ao(index, face_strides, voxels) == quad_ao
+        };
+        let row_width = |start_index: u32, max_width: u32| {
+            let mut width = 0;
+            let mut index = start_index;
+            while width < max_width && can_merge(index) {
+                width += 1;
+                index = index.wrapping_add(face_strides.u_stride);
+            }
+            width
+        };
+
+        let quad_width = row_width(min_index, max_width);
+
+        let mut row_start_stride = min_index.wrapping_add(face_strides.v_stride);
+        let mut quad_height = 1;
+        while quad_height < max_height && row_width(row_start_stride, quad_width) == quad_width {
+            quad_height += 1;
+            row_start_stride = row_start_stride.wrapping_add(face_strides.v_stride);
+        }
+
+        (quad_width, quad_height)
+    }
+
+    const COMPUTES_AO: bool = true;
+
+    unsafe fn quad_ao<A>(min_index: u32, face_strides: &FaceStrides, voxels: &A) -> [u8; 4]
+    where
+        A: VoxelAccess<Voxel = T>,
+    {
+        face_ao(min_index, face_strides, voxels)
+    }
+}
+
+/// The AO values at the 4 corners of a single voxel face, in the same order as
+/// [`OrientedBlockFace::quad_corners`](crate::OrientedBlockFace::quad_corners).
+unsafe fn face_ao<A>(voxel_index: u32, face_strides: &FaceStrides, voxels: &A) -> [u8; 4]
+where
+    A: VoxelAccess,
+    A::Voxel: Voxel,
+{
+    let FaceStrides {
+        u_stride,
+        v_stride,
+        visibility_offset,
+        ..
+    } = *face_strides;
+
+    let layer_index = voxel_index.wrapping_add(visibility_offset);
+    let occludes =
+        |index: u32| voxels.get_unchecked(index).get_visibility() == VoxelVisibility::Opaque;
+    let minus_u = 0u32.wrapping_sub(u_stride);
+    let minus_v = 0u32.wrapping_sub(v_stride);
+
+    [
+        (minus_u, minus_v),
+        (u_stride, minus_v),
+        (minus_u, v_stride),
+        (u_stride, v_stride),
+    ]
+    .map(|(du, dv)| {
+        let side1 = occludes(layer_index.wrapping_add(du));
+        let side2 = occludes(layer_index.wrapping_add(dv));
+        let corner = occludes(layer_index.wrapping_add(du).wrapping_add(dv));
+        vertex_ao(side1, side2, corner)
+    })
+}
+
+/// The classic voxel ambient occlusion value for a single vertex, given whether its two side voxels and corner voxel are
+/// occluders. 3 means no occlusion, 0 means fully occluded.
+#[inline]
+pub fn vertex_ao(side1: bool, side2: bool, corner: bool) -> u8 {
+    if side1 && side2 {
+        0
+    } else {
+        3 - (side1 as u8 + side2 as u8 + corner as u8)
+    }
+}
@@ -0,0 +1,201 @@
+use super::{
+    greedy_quads_for_face, interior_extent, GreedyQuadsBuffer, MergeStrategy, MergeVoxel,
+    VoxelMerger,
+};
+
+use crate::{
+    access::ShapedSlice, bounds::assert_in_bounds, OrientedBlockFace, QuadBuffer, UnorientedQuad,
+    Voxel,
+};
+
+use ilattice::prelude::Extent;
+use ndshape::Shape;
+use rayon::prelude::*;
+
+/// The number of consecutive slices of one face that are meshed by a single task.
+const SLICES_PER_TASK: u32 = 8;
+
+/// The face index, quads, and AO values produced by one task.
+type TaskOutput = (usize, Vec<UnorientedQuad>, Vec<[u8; 4]>);
+
+/// Same as [`greedy_quads`](crate::greedy_quads), but meshes on the [`rayon`] thread pool.
+///
+/// Each face group is split into runs of consecutive slices that are meshed independently, and the results are
+/// concatenated in order, so the output is identical to the serial version.
+pub fn par_greedy_quads<T, S>(
+    voxels: &[T],
+    voxels_shape: &S,
+    min: [u32; 3],
+    max: [u32; 3],
+    faces: &[OrientedBlockFace; 6],
+    output: &mut GreedyQuadsBuffer,
+) where
+    T: MergeVoxel + Sync,
+    S: Shape<3, Coord = u32> + Sync,
+{
+    par_greedy_quads_with_merge_strategy::<_, _, VoxelMerger<T>>(
+        voxels,
+        voxels_shape,
+        min,
+        max,
+        faces,
+        output,
+    )
+}
+
+/// Same as [`greedy_quads_with_merge_strategy`](crate::greedy_quads_with_merge_strategy), but meshes on the [`rayon`]
+/// thread pool. See [`par_greedy_quads`].
+pub fn par_greedy_quads_with_merge_strategy<T, S, Merger>(
+    voxels: &[T],
+    voxels_shape: &S,
+    min: [u32; 3],
+    max: [u32; 3],
+    faces: &[OrientedBlockFace; 6],
+    output: &mut GreedyQuadsBuffer,
+) where
+    T: Voxel + Sync,
+    S: Shape<3, Coord = u32> + Sync,
+    Merger: MergeStrategy<Voxel = T>,
+{
+    assert_in_bounds(voxels, voxels_shape, min, max);
+
+    // Each job uses its own visited mask, so the shared one is left alone.
+    output.quads.reset();
+    let GreedyQuadsBuffer {
+        ao,
+        quads: QuadBuffer { groups },
+        par_visited,
+        ..
+    } = output;
+    for ao_group in ao.iter_mut() {
+        ao_group.clear();
+    }
+
+    let interior = interior_extent(min, max);
+    let shaped_voxels = ShapedSlice {
+        voxels,
+        shape: voxels_shape,
+    };
+
+    let mut tasks = Vec::new();
+    for (face_index, face) in faces.iter().enumerate() {
+        let i_n = face.permutation.axes()[0].index();
+        let first_slice = interior.minimum[i_n];
+        let end_slice = first_slice + interior.shape[i_n];
+        for task_first_slice in (first_slice..end_slice).step_by(SLICES_PER_TASK as usize) {
+            let mut slices_min = interior.minimum;
+            slices_min[i_n] = task_first_slice;
+            let mut slices_shape = interior.shape;
+            slices_shape[i_n] = SLICES_PER_TASK.min(end_slice - task_first_slice);
+            tasks.push((
+                face_index,
+                Extent::from_min_and_shape(slices_min, slices_shape),
+            ));
+        }
+    }
+
+    // Split the tasks into one contiguous run per thread, so that each thread needs a single visited mask. The masks
+    // are reused by later calls with the same `output`.
+    let num_jobs = rayon::current_num_threads().clamp(1, tasks.len().max(1));
+    let tasks_per_job = tasks.len().div_ceil(num_jobs);
+    par_visited.resize_with(num_jobs, Vec::new);
+
+    // Collecting an indexed parallel iterator preserves the order of the jobs.
+    let results: Vec<Vec<TaskOutput>> = par_visited
+        .par_iter_mut()
+        .zip(tasks.par_chunks(tasks_per_job.max(1)))
+        .map(|(visited, tasks)| {
+            if visited.len() != voxels.len() {
+                *visited = vec![false; voxels.len()];
+            }
+            tasks
+                .iter()
+                .map(|&(face_index, slices)| {
+                    let mut quads = Vec::new();
+                    let mut quads_ao = Vec::new();
+                    greedy_quads_for_face::<_, Merger>(
+                        &shaped_voxels,
+                        slices,
+                        &faces[face_index],
+                        visited,
+                        &mut quads,
+                        &mut quads_ao,
+                    );
+                    (face_index, quads, quads_ao)
+                })
+                .collect()
+        })
+        .collect();
+
+    for (face_index, quads, quads_ao) in results.into_iter().flatten() {
+        groups[face_index].extend(quads);
+        ao[face_index].extend(quads_ao);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{noise_voxels, NoiseVoxel, XorShift};
+    use crate::{greedy_quads_with_merge_strategy, AoVoxelMerger, RIGHT_HANDED_Y_UP_CONFIG};
+    use ndshape::{ConstShape, ConstShape3u32};
+
+    type NoiseShape = ConstShape3u32<22, 20, 18>;
+
+    fn check_matches_serial<Merger: MergeStrategy<Voxel = NoiseVoxel>>(
+        voxels: &[NoiseVoxel],
+        min: [u32; 3],
+        max: [u32; 3],
+        parallel: &mut GreedyQuadsBuffer,
+    ) {
+        let faces = &RIGHT_HANDED_Y_UP_CONFIG.faces;
+
+        let mut serial = GreedyQuadsBuffer::new(voxels.len());
+        greedy_quads_with_merge_strategy::<_, _, Merger>(
+            voxels,
+            &NoiseShape {},
+            min,
+            max,
+            faces,
+            &mut serial,
+        );
+
+        par_greedy_quads_with_merge_strategy::<_, _, Merger>(
+            voxels,
+            &NoiseShape {},
+            min,
+            max,
+            faces,
+            parallel,
+        );
+
+        for face_index in 0..6 {
+            let serial_quads = &serial.quads.groups[face_index];
+            let parallel_quads = &parallel.quads.groups[face_index];
+            assert_eq!(serial_quads.len(), parallel_quads.len());
+            for (a, b) in serial_quads.iter().zip(parallel_quads.iter()) {
+                assert_eq!(
+                    (a.minimum, a.width, a.height),
+                    (b.minimum, b.width, b.height)
+                );
+            }
+            assert_eq!(serial.ao[face_index], parallel.ao[face_index]);
+        }
+    }
+
+    #[test]
+    fn matches_serial_greedy_quads() {
+        let voxels = noise_voxels(
+            &mut XorShift(0x2545_f491),
+            NoiseShape::SIZE as usize,
+            &[0, 1, 2, 3],
+        );
+
+        // The parallel buffer is reused to check that stale visited masks don't leak into later calls.
+        let mut parallel = GreedyQuadsBuffer::new(0);
+        for (min, max) in [([0; 3], [21, 19, 17]), ([2, 1, 3], [19, 15, 16])] {
+            check_matches_serial::<VoxelMerger<NoiseVoxel>>(&voxels, min, max, &mut parallel);
+            check_matches_serial::<AoVoxelMerger<NoiseVoxel>>(&voxels, min, max, &mut parallel);
+        }
+    }
+}
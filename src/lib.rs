@@ -5,9 +5,40 @@
 //!
 //! ![Mesh Examples](https://raw.githubusercontent.com/bonsairobo/block-mesh-rs/main/examples-crate/render/mesh_examples.png)
 //!
-//! Two algorithms are included:
+//! Three algorithms are included:
 //! - [`visible_block_faces`](crate::visible_block_faces): very fast but suboptimal meshes
 //! - [`greedy_quads`](crate::greedy_quads): not quite as fast, but far fewer triangles are generated
+//! - [`binary_greedy_quads`](crate::binary_greedy_quads): the same output as [`greedy_quads`](crate::greedy_quads), but
+//!   faster, using bitmasks for chunks of up to 64^3 voxels (including padding)
+//!
+//! To mesh a chunk without first copying the borders of its neighbours into a padded array, see
+//! [`ChunkNeighborhood`](crate::ChunkNeighborhood).
+//!
+//! Chunks placed anywhere in a world, including at negative coordinates, can be meshed with a world-space extent by
+//! [`greedy_quads_in_world`](crate::greedy_quads_in_world), and [`world_translation`](crate::world_translation) or
+//! [`relative_translation`](crate::relative_translation) place the chunk-relative meshes in large worlds without losing
+//! `f32` precision.
+//!
+//! The quads of a prefab placed in any of 24 orientations, mirrored or not, can be reused with
+//! [`AxisTransform::transform_quads`](crate::AxisTransform::transform_quads) instead of meshing it again.
+//!
+//! For block picking, [`raycast`](crate::raycast) finds the first voxel and face hit by a ray, with the same visibility as
+//! the meshers.
+//!
+//! Voxels that aren't full cubes, like slabs, stairs, and plants, can be meshed into a [`MeshBuffer`](crate::MeshBuffer) with
+//! [`block_model_faces`](crate::block_model_faces).
+//!
+//! [`MeshBuilder`](crate::MeshBuilder) turns the quads from any of these algorithms into vertex and index arrays, and
+//! [`build_without_t_junctions`](crate::build_without_t_junctions) does the same without the cracks that T-junctions between
+//! greedy quads can cause.
+//! [`atlas_uvs`](crate::atlas_uvs) and [`array_texture_layers`](crate::array_texture_layers) add the texture attributes for
+//! texture atlases and array textures.
+//!
+//! MagicaVoxel `.vox` files can be loaded into padded arrays of [`VoxVoxel`](crate::VoxVoxel) with
+//! [`read_vox`](crate::read_vox).
+//!
+//! The `block-mesh` binary does this for whole files, writing OBJ or glTF binary meshes. Models are turned from the +Z up
+//! of MagicaVoxel to the up axis of the chosen coordinate config. Run `cargo run --bin block-mesh -- --help` for usage.
 //!
 //! Benchmarks show that [`visible_block_faces`](crate::visible_block_faces) generates about 40 million quads per second on a
 //! single core of a 2.5 GHz Intel Core i7. Assuming spherical input data, [`greedy_quads`](crate::greedy_quads) can generate a
@@ -77,17 +108,45 @@
 //! assert!(buffer.quads.num_quads() > 0);
 //! ```
 
+mod access;
+mod atlas;
+mod binary_greedy;
 mod bounds;
 mod buffer;
+mod export;
 pub mod geometry;
 mod greedy;
+mod mesh;
+mod model;
+mod neighborhood;
+mod packed;
+mod raycast;
 mod simple;
+mod stats;
+mod t_junction;
+#[cfg(test)]
+mod test_util;
+mod vox;
+mod world;
 
+pub use access::*;
+pub use atlas::*;
+pub use binary_greedy::*;
 pub use buffer::*;
+pub use export::*;
 #[doc(inline)]
 pub use geometry::*;
 pub use greedy::*;
+pub use mesh::*;
+pub use model::*;
+pub use neighborhood::*;
+pub use packed::*;
+pub use raycast::*;
 pub use simple::*;
+pub use stats::*;
+pub use t_junction::*;
+pub use vox::*;
+pub use world::*;
 
 pub use ilattice;
 pub use ndshape;
@@ -107,6 +166,51 @@ pub enum VoxelVisibility {
 /// how to generate geometry for this voxel.
 pub trait Voxel {
     fn get_visibility(&self) -> VoxelVisibility;
+
+    /// The visibility of just the side of this voxel facing in the `face` direction. This determines both whether that face
+    /// is meshed and whether it occludes the neighbouring voxel's face.
+    ///
+    /// Override this for blocks like slabs, doors, or one-way glass that only occlude some of their neighbours. If
+    /// [`Voxel::get_visibility`] returns [`VoxelVisibility::Empty`], the voxel never gets faces of its own, but the
+    /// visibility of its sides still decides whether they hide the faces of its neighbours. This is how a
+    /// [`ModelVoxel`] hides the cube faces against it. By default, all faces have the same visibility as the whole
+    /// voxel.
+    #[inline]
+    fn get_face_visibility(&self, face: SignedAxis) -> VoxelVisibility {
+        let _ = face;
+        self.get_visibility()
+    }
+
+    /// The group of [`VoxelVisibility::Translucent`] voxels this voxel belongs to, e.g. a material ID.
+    ///
+    /// A face between two translucent voxels is only meshed if they are in different groups, so a glass block under water
+    /// is visible, but the faces inside a body of water are not. By default, all translucent voxels are in the same group.
+    #[inline]
+    fn get_translucency_group(&self) -> u32 {
+        0
+    }
+}
+
+/// Returns true iff the `face` of `voxel`, which touches `neighbour`, should be meshed. This is the rule shared by all
+/// meshing algorithms.
+#[inline]
+pub(crate) fn face_is_visible<T, N>(voxel: &T, neighbour: &N, face: SignedAxis) -> bool
+where
+    T: Voxel,
+    N: Voxel,
+{
+    match (
+        voxel.get_face_visibility(face),
+        neighbour.get_face_visibility(face.opposite()),
+    ) {
+        (VoxelVisibility::Empty, _) => false,
+        (_, VoxelVisibility::Empty) => true,
+        (VoxelVisibility::Opaque, VoxelVisibility::Translucent) => true,
+        (VoxelVisibility::Translucent, VoxelVisibility::Translucent) => {
+            voxel.get_translucency_group() != neighbour.get_translucency_group()
+        }
+        (_, VoxelVisibility::Opaque) => false,
+    }
 }
 
 /// Used as a dummy for functions that must wrap a voxel
@@ -118,6 +222,16 @@ impl<'a, T: Voxel> Voxel for IdentityVoxel<'a, T> {
     fn get_visibility(&self) -> VoxelVisibility {
         self.0.get_visibility()
     }
+
+    #[inline]
+    fn get_face_visibility(&self, face: SignedAxis) -> VoxelVisibility {
+        self.0.get_face_visibility(face)
+    }
+
+    #[inline]
+    fn get_translucency_group(&self) -> u32 {
+        self.0.get_translucency_group()
+    }
 }
 
 impl<'a, T: Voxel> From<&'a T> for IdentityVoxel<'a, T> {
@@ -0,0 +1,232 @@
+use crate::{QuadBuffer, QuadCoordinateConfig, UnitQuadBuffer, UnorientedQuad};
+
+/// Vertex attributes and triangle indices of a mesh, in the separate arrays that most renderers expect.
+///
+/// Every vertex has one entry in each of `positions`, `normals`, and `tex_coords`. Every 3 `indices` make a triangle, with
+/// front faces wound counterclockwise as in
+/// [`OrientedBlockFace::quad_mesh_indices`](crate::OrientedBlockFace::quad_mesh_indices).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshBuffer {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub tex_coords: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.positions.clear();
+        self.normals.clear();
+        self.tex_coords.clear();
+        self.indices.clear();
+    }
+
+    /// Returns the number of vertices.
+    pub fn num_vertices(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns the number of triangles.
+    pub fn num_triangles(&self) -> usize {
+        self.indices.len() / 3
+    }
+}
+
+/// Quads grouped by the face of a [`QuadCoordinateConfig`] they belong to, like [`QuadBuffer`] and [`UnitQuadBuffer`].
+pub trait QuadGroups {
+    /// Returns the total count of quads across all groups.
+    fn num_quads(&self) -> usize;
+
+    /// Calls `f` with the group index and each quad, in order.
+    fn for_each_quad(&self, f: impl FnMut(usize, UnorientedQuad));
+}
+
+impl QuadGroups for QuadBuffer {
+    fn num_quads(&self) -> usize {
+        QuadBuffer::num_quads(self)
+    }
+
+    fn for_each_quad(&self, mut f: impl FnMut(usize, UnorientedQuad)) {
+        for (group_index, group) in self.groups.iter().enumerate() {
+            for quad in group.iter() {
+                f(group_index, *quad);
+            }
+        }
+    }
+}
+
+impl QuadGroups for UnitQuadBuffer {
+    fn num_quads(&self) -> usize {
+        UnitQuadBuffer::num_quads(self)
+    }
+
+    fn for_each_quad(&self, mut f: impl FnMut(usize, UnorientedQuad)) {
+        for (group_index, group) in self.groups.iter().enumerate() {
+            for quad in group.iter() {
+                f(group_index, (*quad).into());
+            }
+        }
+    }
+}
+
+/// Assembles the quads from any of the meshers into a [`MeshBuffer`] in a single pass.
+///
+/// ```
+/// # use block_mesh::{MeshBuilder, QuadBuffer, RIGHT_HANDED_Y_UP_CONFIG};
+/// # let quads = QuadBuffer::new();
+/// let mesh = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG)
+///     .voxel_size(0.5)
+///     .flip_v(true)
+///     .build(&quads);
+/// ```
+#[derive(Clone)]
+pub struct MeshBuilder {
+    pub(crate) config: QuadCoordinateConfig,
+    pub(crate) voxel_size: f32,
+    pub(crate) flip_v: bool,
+}
+
+impl MeshBuilder {
+    /// Creates a builder for the coordinate system of `config`, with a voxel size of 1 and without flipping V.
+    pub fn new(config: &QuadCoordinateConfig) -> Self {
+        Self {
+            config: config.clone(),
+            voxel_size: 1.0,
+            flip_v: false,
+        }
+    }
+
+    /// Sets the length of a voxel's edge in the mesh. Positions are scaled by this amount.
+    pub fn voxel_size(mut self, voxel_size: f32) -> Self {
+        self.voxel_size = voxel_size;
+        self
+    }
+
+    /// Sets whether V texture coordinates are flipped, for renderers that put (0, 0) at the top left of textures. See
+    /// [`OrientedBlockFace::tex_coords`](crate::OrientedBlockFace::tex_coords).
+    pub fn flip_v(mut self, flip_v: bool) -> Self {
+        self.flip_v = flip_v;
+        self
+    }
+
+    /// Builds a new mesh from `quads`.
+    pub fn build(&self, quads: &impl QuadGroups) -> MeshBuffer {
+        let mut output = MeshBuffer::new();
+        self.build_into(quads, &mut output);
+        output
+    }
+
+    /// Same as [`MeshBuilder::build`], but reuses the allocations of `output`. Any previous contents are cleared.
+    pub fn build_into(&self, quads: &impl QuadGroups, output: &mut MeshBuffer) {
+        output.reset();
+        let num_quads = quads.num_quads();
+        output.positions.reserve(4 * num_quads);
+        output.normals.reserve(4 * num_quads);
+        output.tex_coords.reserve(4 * num_quads);
+        output.indices.reserve(6 * num_quads);
+
+        let normals = self.config.faces.map(|face| face.quad_mesh_normals());
+        quads.for_each_quad(|group_index, quad| {
+            let face = &self.config.faces[group_index];
+            output
+                .indices
+                .extend_from_slice(&face.quad_mesh_indices(output.positions.len() as u32));
+            output
+                .positions
+                .extend_from_slice(&face.quad_mesh_positions(&quad, self.voxel_size));
+            output.normals.extend_from_slice(&normals[group_index]);
+            output.tex_coords.extend_from_slice(&face.tex_coords(
+                self.config.u_flip_face,
+                self.flip_v,
+                &quad,
+            ));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bool_voxels, BoolVoxel};
+    use crate::{greedy_quads, visible_block_faces, GreedyQuadsBuffer, RIGHT_HANDED_Y_UP_CONFIG};
+    use ndshape::ConstShape3u32;
+
+    type SampleShape = ConstShape3u32<6, 6, 6>;
+
+    fn samples() -> Vec<BoolVoxel> {
+        bool_voxels(&SampleShape {}, |[x, y, z]| {
+            (1..5).contains(&x) && (1..4).contains(&y) && (2..4).contains(&z)
+        })
+    }
+
+    #[test]
+    fn matches_hand_written_loop() {
+        let samples = samples();
+        let config = &RIGHT_HANDED_Y_UP_CONFIG;
+        let mut buffer = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads(
+            &samples,
+            &SampleShape {},
+            [0; 3],
+            [5; 3],
+            &config.faces,
+            &mut buffer,
+        );
+
+        let mut expected = MeshBuffer::new();
+        for (group, face) in buffer.quads.groups.iter().zip(config.faces.iter()) {
+            for quad in group.iter() {
+                expected
+                    .indices
+                    .extend_from_slice(&face.quad_mesh_indices(expected.positions.len() as u32));
+                expected
+                    .positions
+                    .extend_from_slice(&face.quad_mesh_positions(quad, 0.5));
+                expected
+                    .normals
+                    .extend_from_slice(&face.quad_mesh_normals());
+                expected.tex_coords.extend_from_slice(&face.tex_coords(
+                    config.u_flip_face,
+                    true,
+                    quad,
+                ));
+            }
+        }
+
+        let mesh = MeshBuilder::new(config)
+            .voxel_size(0.5)
+            .flip_v(true)
+            .build(&buffer.quads);
+        assert_eq!(mesh, expected);
+        assert_eq!(mesh.num_triangles(), 2 * buffer.quads.num_quads());
+    }
+
+    #[test]
+    fn builds_unit_quads_into_reused_buffer() {
+        let samples = samples();
+        let config = &RIGHT_HANDED_Y_UP_CONFIG;
+        let mut buffer = UnitQuadBuffer::new();
+        visible_block_faces(
+            &samples,
+            &SampleShape {},
+            [0; 3],
+            [5; 3],
+            &config.faces,
+            &mut buffer,
+        );
+
+        let builder = MeshBuilder::new(config);
+        let mut mesh = builder.build(&buffer);
+        assert_eq!(mesh.num_vertices(), 4 * buffer.num_quads());
+
+        let expected = mesh.clone();
+        let capacity = mesh.positions.capacity();
+        builder.build_into(&buffer, &mut mesh);
+        assert_eq!(mesh, expected);
+        assert_eq!(mesh.positions.capacity(), capacity);
+    }
+}
@@ -0,0 +1,597 @@
+use crate::{
+    bounds::assert_in_bounds, geometry::quad_indices, greedy::interior_extent, Axis, MeshBuffer,
+    OrientedBlockFace, QuadCoordinateConfig, SignedAxis, Voxel, VoxelVisibility,
+};
+
+use ilattice::glam::Vec3;
+use ndshape::Shape;
+
+/// An axis-aligned box inside of a voxel, in voxel units. The full voxel is `[0, 1]` on every axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModelBox {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+impl ModelBox {
+    /// The box covering the whole voxel.
+    pub const FULL: Self = Self::new([0.0; 3], [1.0; 3]);
+
+    pub const fn new(min: [f32; 3], max: [f32; 3]) -> Self {
+        Self { min, max }
+    }
+
+    /// The half of the voxel on the `side` of its centre.
+    pub fn half(side: SignedAxis) -> Self {
+        let mut half = Self::FULL;
+        let i = side.unsigned_axis().index();
+        if side.signum() > 0 {
+            half.min[i] = 0.5;
+        } else {
+            half.max[i] = 0.5;
+        }
+        half
+    }
+
+    /// The overlap of `self` and `other`, which may be empty.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intersection = *self;
+        for i in 0..3 {
+            intersection.min[i] = self.min[i].max(other.min[i]);
+            intersection.max[i] = self.max[i].min(other.max[i]);
+        }
+        intersection
+    }
+
+    /// Returns the plane coordinate of the face of this box on `side` and the rectangle it covers, as `[min, max]` on the
+    /// other two axes in cyclic order.
+    fn face(&self, side: SignedAxis) -> (f32, [[f32; 2]; 2]) {
+        let i = side.unsigned_axis().index();
+        let [j, k] = [(i + 1) % 3, (i + 2) % 3];
+        let plane = if side.signum() > 0 {
+            self.max[i]
+        } else {
+            self.min[i]
+        };
+        (
+            plane,
+            [[self.min[j], self.min[k]], [self.max[j], self.max[k]]],
+        )
+    }
+}
+
+/// Returns true iff a box face on `side` with the plane coordinate `plane` lies on that side of the voxel.
+fn is_on_side(plane: f32, side: SignedAxis) -> bool {
+    if side.signum() > 0 {
+        plane >= 1.0
+    } else {
+        plane <= 0.0
+    }
+}
+
+/// The shape of a voxel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlockModel<'a> {
+    /// A full cube, meshed by the cube meshers like [`greedy_quads`](crate::greedy_quads).
+    Cube,
+    /// Half of a cube, on the given side of the voxel. [`SignedAxis::NegY`] is a bottom slab in a Y-up coordinate system.
+    Slab(SignedAxis),
+    /// A slab on the `bottom` side, with a quarter of a cube on top of it against the `back` side. The axes of `bottom` and
+    /// `back` must differ.
+    Stairs {
+        bottom: SignedAxis,
+        back: SignedAxis,
+    },
+    /// Two quads crossing diagonally through the voxel, both parallel to the `up` axis, like plants in Minecraft. Both
+    /// sides of each quad are meshed, and they are never culled.
+    Cross { up: Axis },
+    /// Any number of boxes.
+    Boxes(&'a [ModelBox]),
+}
+
+impl BlockModel<'_> {
+    /// Calls `f` with the boxes that make up this model. [`BlockModel::Cross`] has no boxes.
+    pub fn with_boxes<R>(&self, f: impl FnOnce(&[ModelBox]) -> R) -> R {
+        match *self {
+            Self::Cube => f(&[ModelBox::FULL]),
+            Self::Slab(side) => f(&[ModelBox::half(side)]),
+            Self::Stairs { bottom, back } => {
+                assert_ne!(
+                    bottom.unsigned_axis(),
+                    back.unsigned_axis(),
+                    "stairs need perpendicular bottom and back sides"
+                );
+                let step = ModelBox::half(bottom.opposite()).intersection(&ModelBox::half(back));
+                f(&[ModelBox::half(bottom), step])
+            }
+            Self::Cross { .. } => f(&[]),
+            Self::Boxes(boxes) => f(boxes),
+        }
+    }
+
+    /// Returns true iff the boxes of this model together cover the whole `side` of the voxel.
+    pub fn covers_side(&self, side: SignedAxis) -> bool {
+        self.with_boxes(|boxes| {
+            let rects: Vec<[[f32; 2]; 2]> = boxes
+                .iter()
+                .map(|model_box| model_box.face(side))
+                .filter(|&(plane, _)| is_on_side(plane, side))
+                .map(|(_, rect)| rect)
+                .collect();
+
+            // Split the side into cells at every edge of a rectangle. Each cell is either inside of a rectangle or not.
+            let cuts = |i: usize| {
+                let mut cuts: Vec<f32> = rects
+                    .iter()
+                    .flat_map(|rect| [rect[0][i], rect[1][i]])
+                    .filter(|c| (0.0..=1.0).contains(c))
+                    .chain([0.0, 1.0])
+                    .collect();
+                cuts.sort_by(f32::total_cmp);
+                cuts.dedup();
+                cuts
+            };
+            let (u_cuts, v_cuts) = (cuts(0), cuts(1));
+            u_cuts.windows(2).all(|u| {
+                v_cuts.windows(2).all(|v| {
+                    let centre = [(u[0] + u[1]) / 2.0, (v[0] + v[1]) / 2.0];
+                    rects.iter().any(|rect| {
+                        (0..2).all(|i| rect[0][i] <= centre[i] && centre[i] <= rect[1][i])
+                    })
+                })
+            })
+        })
+    }
+}
+
+/// A voxel that may have a shape other than a full cube.
+///
+/// Voxels with a [`BlockModel::Cube`] model are meshed by the cube meshers as usual. Voxels with any other model must
+/// report [`VoxelVisibility::Empty`] from [`Voxel::get_visibility`] so the cube meshers don't mesh them as cubes, and
+/// are meshed by [`block_model_faces`] instead.
+///
+/// So that the cube meshers still cull cube faces against the sides that a model covers, like the top of a stone block
+/// under a bottom slab, implement [`Voxel::get_face_visibility`] with [`model_face_visibility`].
+pub trait ModelVoxel: Voxel {
+    fn get_model(&self) -> BlockModel<'_>;
+}
+
+/// The visibility of the `face` side of `voxel`, for implementing [`Voxel::get_face_visibility`] on a [`ModelVoxel`].
+///
+/// A [`BlockModel::Cube`] has the visibility of the whole voxel. Any other model is [`VoxelVisibility::Opaque`] on the
+/// sides that it [covers](BlockModel::covers_side) and [`VoxelVisibility::Empty`] on the rest, just like
+/// [`block_model_faces`] treats the boxes of models as opaque.
+pub fn model_face_visibility<T>(voxel: &T, face: SignedAxis) -> VoxelVisibility
+where
+    T: ModelVoxel,
+{
+    match voxel.get_model() {
+        BlockModel::Cube => voxel.get_visibility(),
+        model if model.covers_side(face) => VoxelVisibility::Opaque,
+        _ => VoxelVisibility::Empty,
+    }
+}
+
+/// Meshes the faces of all voxels on the interior of `[min, max]` that don't have a [`BlockModel::Cube`] model, appending
+/// them to `output`. Like the cube meshers, `voxels` must contain a 1-voxel boundary of padding.
+///
+/// A face of a model box is culled if it is fully covered by an opaque face of a neighbouring voxel, by the opposite face of
+/// a box of a neighbouring model, or by the opposite face of another box in the same model. The boxes of models are treated
+/// as opaque for culling.
+///
+/// Positions are in voxel units, so the voxel at `p` spans `p` to `p + 1`. The faces of model boxes use the same
+/// [`OrientedBlockFace`] as the corresponding cube face of `config`, so normals, winding, and UVs follow the same conventions
+/// as [`OrientedBlockFace::tex_coords`] for a unit quad. The UVs of a box face that only covers part of the voxel are the same
+/// part of the texture.
+pub fn block_model_faces<T, S>(
+    voxels: &[T],
+    voxels_shape: &S,
+    min: [u32; 3],
+    max: [u32; 3],
+    config: &QuadCoordinateConfig,
+    flip_v: bool,
+    output: &mut MeshBuffer,
+) where
+    T: ModelVoxel,
+    S: Shape<3, Coord = u32>,
+{
+    assert_in_bounds(voxels, voxels_shape, min, max);
+
+    let interior = interior_extent(min, max);
+
+    let faces = SignedAxis::ALL.map(|side| {
+        *config
+            .faces
+            .iter()
+            .find(|face| face.normal_axis() == side)
+            .expect("config is missing a face")
+    });
+    let kernel_strides = SignedAxis::ALL
+        .map(|side| voxels_shape.linearize(side.get_unit_vector().as_uvec3().to_array()));
+
+    for p in interior.iter3() {
+        let p_index = voxels_shape.linearize(p.to_array());
+        let p_voxel = unsafe { voxels.get_unchecked(p_index as usize) };
+        let offset = p.as_vec3();
+
+        match p_voxel.get_model() {
+            BlockModel::Cube => {}
+            BlockModel::Cross { up } => push_cross(offset, up, config, flip_v, output),
+            model => model.with_boxes(|boxes| {
+                for (box_index, model_box) in boxes.iter().enumerate() {
+                    for (side_index, side) in SignedAxis::ALL.into_iter().enumerate() {
+                        let neighbour_index = p_index.wrapping_add(kernel_strides[side_index]);
+                        let neighbour = unsafe { voxels.get_unchecked(neighbour_index as usize) };
+                        if !box_face_is_covered(boxes, box_index, side, neighbour) {
+                            push_box_face(
+                                offset,
+                                model_box,
+                                &faces[side_index],
+                                config.u_flip_face,
+                                flip_v,
+                                output,
+                            );
+                        }
+                    }
+                }
+            }),
+        }
+    }
+}
+
+fn box_face_is_covered<T>(
+    boxes: &[ModelBox],
+    box_index: usize,
+    side: SignedAxis,
+    neighbour: &T,
+) -> bool
+where
+    T: ModelVoxel,
+{
+    let (plane, rect) = boxes[box_index].face(side);
+    let covers = |(other_plane, other_rect): (f32, [[f32; 2]; 2]), plane: f32| {
+        other_plane == plane
+            && (0..2).all(|i| other_rect[0][i] <= rect[0][i] && rect[1][i] <= other_rect[1][i])
+    };
+
+    if !is_on_side(plane, side) {
+        return boxes
+            .iter()
+            .enumerate()
+            .any(|(i, other)| i != box_index && covers(other.face(side.opposite()), plane));
+    }
+
+    if neighbour.get_face_visibility(side.opposite()) == VoxelVisibility::Opaque {
+        return true;
+    }
+    match neighbour.get_model() {
+        // Cube neighbours are only covering when they're opaque.
+        BlockModel::Cube => false,
+        model => {
+            let neighbour_plane = 1.0 - plane;
+            model.with_boxes(|neighbour_boxes| {
+                neighbour_boxes
+                    .iter()
+                    .any(|other| covers(other.face(side.opposite()), neighbour_plane))
+            })
+        }
+    }
+}
+
+fn push_box_face(
+    offset: Vec3,
+    model_box: &ModelBox,
+    face: &OrientedBlockFace,
+    u_flip_face: Axis,
+    flip_v: bool,
+    output: &mut MeshBuffer,
+) {
+    let [n_axis, u_axis, v_axis] = face.permutation.axes();
+    let [i_n, i_u, i_v] = [n_axis.index(), u_axis.index(), v_axis.index()];
+    let flip_u = face.flip_u(u_flip_face);
+
+    let mut corner = [0.0; 3];
+    corner[i_n] = if face.n_sign > 0 {
+        model_box.max[i_n]
+    } else {
+        model_box.min[i_n]
+    };
+
+    let start = output.positions.len() as u32;
+    for [s, t] in [[0, 0], [1, 0], [0, 1], [1, 1]] {
+        corner[i_u] = [model_box.min[i_u], model_box.max[i_u]][s];
+        corner[i_v] = [model_box.min[i_v], model_box.max[i_v]][t];
+        output
+            .positions
+            .push((offset + Vec3::from(corner)).to_array());
+        output.tex_coords.push([
+            if flip_u {
+                1.0 - corner[i_u]
+            } else {
+                corner[i_u]
+            },
+            if flip_v {
+                1.0 - corner[i_v]
+            } else {
+                corner[i_v]
+            },
+        ]);
+    }
+    output
+        .normals
+        .extend([face.signed_normal().as_vec3().to_array(); 4]);
+    output
+        .indices
+        .extend_from_slice(&face.quad_mesh_indices(start));
+}
+
+fn push_cross(
+    offset: Vec3,
+    up: Axis,
+    config: &QuadCoordinateConfig,
+    flip_v: bool,
+    output: &mut MeshBuffer,
+) {
+    let i_up = up.index();
+    let up = up.get_unit_vector().as_vec3();
+    let (mut b, mut c) = (Vec3::ZERO, Vec3::ZERO);
+    b[(i_up + 1) % 3] = 1.0;
+    c[(i_up + 2) % 3] = 1.0;
+
+    // The sign of (texture right x texture up) . normal on the cube faces of this config, which we have to match to avoid
+    // mirroring the texture.
+    let face = &config.faces[0];
+    let flip_u_sign = if face.flip_u(config.u_flip_face) {
+        -1
+    } else {
+        1
+    };
+    let orientation = flip_u_sign * face.permutation.sign() * face.n_sign;
+
+    // Each diagonal plane runs from `start` along `along`, and is meshed once for each side.
+    for (start, along) in [(Vec3::ZERO, b + c), (c, b - c)] {
+        let plane_normal = along.cross(up).normalize();
+        for normal in [plane_normal, -plane_normal] {
+            // Pick the direction of texture U so that (U x up) . normal has the same sign as the cube faces.
+            let (first, right) = if (orientation > 0) == (along.cross(up).dot(normal) > 0.0) {
+                (start, along)
+            } else {
+                (start + along, -along)
+            };
+            let base = offset + first;
+            let start_index = output.positions.len() as u32;
+            output
+                .positions
+                .extend([base, base + right, base + up, base + right + up].map(|p| p.to_array()));
+            output.normals.extend([normal.to_array(); 4]);
+            let [v0, v1] = if flip_v { [1.0, 0.0] } else { [0.0, 1.0] };
+            output
+                .tex_coords
+                .extend([[0.0, v0], [1.0, v0], [0.0, v1], [1.0, v1]]);
+            output
+                .indices
+                .extend_from_slice(&quad_indices(start_index, orientation > 0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        binary_greedy_quads, greedy_quads, visible_block_faces, GreedyQuadsBuffer, MergeVoxel,
+        UnitQuadBuffer, RIGHT_HANDED_Y_UP_CONFIG,
+    };
+    use ndshape::{ConstShape, ConstShape3u32};
+
+    const WALL: [ModelBox; 1] = [ModelBox::new([0.0; 3], [1.0, 1.0, 0.5])];
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    enum Block {
+        Air,
+        Stone,
+        Slab,
+        Stairs,
+        Plant,
+        Wall,
+    }
+
+    impl Voxel for Block {
+        fn get_visibility(&self) -> VoxelVisibility {
+            match self {
+                Block::Stone => VoxelVisibility::Opaque,
+                _ => VoxelVisibility::Empty,
+            }
+        }
+
+        fn get_face_visibility(&self, face: SignedAxis) -> VoxelVisibility {
+            model_face_visibility(self, face)
+        }
+    }
+
+    impl MergeVoxel for Block {
+        type MergeValue = Self;
+        type MergeValueFacingNeighbour = ();
+
+        fn merge_value(&self) -> Self::MergeValue {
+            *self
+        }
+
+        fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour {}
+    }
+
+    impl ModelVoxel for Block {
+        fn get_model(&self) -> BlockModel<'_> {
+            match self {
+                Block::Air | Block::Stone => BlockModel::Cube,
+                Block::Slab => BlockModel::Slab(SignedAxis::NegY),
+                Block::Stairs => BlockModel::Stairs {
+                    bottom: SignedAxis::NegY,
+                    back: SignedAxis::PosZ,
+                },
+                Block::Plant => BlockModel::Cross { up: Axis::Y },
+                Block::Wall => BlockModel::Boxes(&WALL),
+            }
+        }
+    }
+
+    type SampleShape = ConstShape3u32<4, 4, 4>;
+
+    fn place(placed: &[([u32; 3], Block)]) -> [Block; SampleShape::SIZE as usize] {
+        let mut voxels = [Block::Air; SampleShape::SIZE as usize];
+        for &(p, block) in placed {
+            voxels[SampleShape {}.linearize(p) as usize] = block;
+        }
+        voxels
+    }
+
+    fn mesh(placed: &[([u32; 3], Block)]) -> MeshBuffer {
+        let voxels = place(placed);
+        let mut output = MeshBuffer::new();
+        block_model_faces(
+            &voxels,
+            &SampleShape {},
+            [0; 3],
+            [3; 3],
+            &RIGHT_HANDED_Y_UP_CONFIG,
+            false,
+            &mut output,
+        );
+        output
+    }
+
+    /// Checks that every triangle is wound counterclockwise around its vertex normals.
+    fn assert_winding_matches_normals(mesh: &MeshBuffer) {
+        for triangle in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(mesh.positions[triangle[i] as usize]));
+            let normal = Vec3::from(mesh.normals[triangle[0] as usize]);
+            assert!((b - a).cross(c - a).dot(normal) > 0.0);
+        }
+    }
+
+    #[test]
+    fn slab_on_floor_culls_bottom_face() {
+        let mesh = mesh(&[([1, 1, 1], Block::Slab), ([1, 0, 1], Block::Stone)]);
+
+        assert_eq!(mesh.num_vertices(), 5 * 4);
+        assert!(mesh.normals.iter().all(|n| *n != [0.0, -1.0, 0.0]));
+        let top_y = mesh.positions.iter().map(|p| p[1]).fold(0.0, f32::max);
+        assert_eq!(top_y, 1.5);
+        assert_winding_matches_normals(&mesh);
+    }
+
+    #[test]
+    fn adjacent_slabs_cull_shared_sides() {
+        let mesh = mesh(&[([1, 1, 1], Block::Slab), ([2, 1, 1], Block::Slab)]);
+
+        assert_eq!(mesh.num_vertices(), 2 * 5 * 4);
+    }
+
+    #[test]
+    fn stairs_and_slab_cull_only_covered_faces() {
+        // The back of the stairs step isn't covered by the slab behind it.
+        let mesh = mesh(&[([1, 1, 1], Block::Stairs), ([1, 1, 2], Block::Slab)]);
+
+        // Stairs: 6 slab faces + 5 step faces, minus the slab's back. Slab: 6 faces minus its front.
+        assert_eq!(mesh.num_vertices(), (6 + 5 - 1 + 5) * 4);
+        assert_winding_matches_normals(&mesh);
+    }
+
+    #[test]
+    fn slab_tex_coords_cover_part_of_texture() {
+        let mesh = mesh(&[([1, 1, 1], Block::Slab)]);
+
+        // The +Z face of a bottom slab shows the bottom half of the texture.
+        let start = mesh
+            .normals
+            .iter()
+            .position(|n| *n == [0.0, 0.0, 1.0])
+            .unwrap();
+        assert_eq!(
+            mesh.tex_coords[start..start + 4],
+            [[0.0, 0.0], [1.0, 0.0], [0.0, 0.5], [1.0, 0.5]]
+        );
+    }
+
+    #[test]
+    fn cross_is_double_sided() {
+        let mesh = mesh(&[([1, 1, 1], Block::Plant), ([1, 0, 1], Block::Stone)]);
+
+        assert_eq!(mesh.num_vertices(), 4 * 4);
+        assert_winding_matches_normals(&mesh);
+        for quad in 0..4 {
+            let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(mesh.positions[4 * quad + i]));
+            let normal = Vec3::from(mesh.normals[4 * quad]);
+            // The texture isn't mirrored: (U x V) points along the normal, like on the cube faces of a right-handed config.
+            assert!((b - a).cross(c - a).dot(normal) > 0.0);
+            assert_eq!(mesh.tex_coords[4 * quad], [0.0, 0.0]);
+            assert_eq!(mesh.tex_coords[4 * quad + 3], [1.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn cubes_are_not_meshed() {
+        let mesh = mesh(&[([1, 1, 1], Block::Stone)]);
+
+        assert_eq!(mesh, MeshBuffer::new());
+    }
+
+    #[test]
+    fn covered_sides() {
+        let stairs = BlockModel::Stairs {
+            bottom: SignedAxis::NegY,
+            back: SignedAxis::PosZ,
+        };
+        // The back is covered by the slab and the step together.
+        let covered: Vec<_> = SignedAxis::ALL
+            .into_iter()
+            .filter(|&side| stairs.covers_side(side))
+            .collect();
+        assert_eq!(covered, [SignedAxis::NegY, SignedAxis::PosZ]);
+
+        assert!(SignedAxis::ALL
+            .into_iter()
+            .all(|side| BlockModel::Cube.covers_side(side)));
+        assert!(!SignedAxis::ALL
+            .into_iter()
+            .any(|side| BlockModel::Cross { up: Axis::Y }.covers_side(side)));
+        assert!(BlockModel::Boxes(&WALL).covers_side(SignedAxis::NegZ));
+        assert!(!BlockModel::Boxes(&WALL).covers_side(SignedAxis::NegX));
+    }
+
+    #[test]
+    fn cube_faces_are_culled_against_covered_sides() {
+        // A slab on top, a wall behind, and the low side of stairs to the right of the stone.
+        let voxels = place(&[
+            ([1, 1, 1], Block::Stone),
+            ([1, 2, 1], Block::Slab),
+            ([1, 1, 2], Block::Wall),
+            ([2, 1, 1], Block::Stairs),
+        ]);
+        let faces = &RIGHT_HANDED_Y_UP_CONFIG.faces;
+        let expected = [true, true, true, true, false, false];
+
+        let mut unit_quads = UnitQuadBuffer::new();
+        visible_block_faces(
+            &voxels,
+            &SampleShape {},
+            [0; 3],
+            [3; 3],
+            faces,
+            &mut unit_quads,
+        );
+        let mut greedy = GreedyQuadsBuffer::new(voxels.len());
+        greedy_quads(&voxels, &SampleShape {}, [0; 3], [3; 3], faces, &mut greedy);
+        let mut binary = GreedyQuadsBuffer::new(voxels.len());
+        binary_greedy_quads(&voxels, &SampleShape {}, [0; 3], [3; 3], faces, &mut binary);
+
+        for (face_index, visible) in expected.into_iter().enumerate() {
+            assert_eq!(!unit_quads.groups[face_index].is_empty(), visible);
+            assert_eq!(!greedy.quads.groups[face_index].is_empty(), visible);
+            assert_eq!(
+                binary.quads.groups[face_index],
+                greedy.quads.groups[face_index]
+            );
+        }
+    }
+}
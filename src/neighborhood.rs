@@ -0,0 +1,346 @@
+use crate::greedy::{greedy_quads_for_face, VoxelMerger};
+use crate::simple::visible_block_faces_for_extent;
+use crate::{
+    GreedyQuadsBuffer, IdentityVoxel, MergeStrategy, MergeVoxel, OrientedBlockFace, QuadBuffer,
+    UnitQuadBuffer, Voxel, VoxelAccess,
+};
+
+use ilattice::glam::UVec3;
+use ilattice::prelude::Extent;
+use ndshape::{RuntimePow2Shape, Shape};
+
+/// A chunk of voxels and up to 26 neighbouring chunks of the same shape, borrowed in place.
+///
+/// The meshers usually require a single array with a 1-voxel apron of neighbouring voxels. Instead of copying the borders of
+/// the neighbours into such an array, [`greedy_quads_neighborhood`] and [`visible_block_faces_neighborhood`] read across
+/// chunk borders directly.
+///
+/// Coordinates are in the space of that padded array: the centre chunk covers `[1, chunk_shape]`, and the apron covers the
+/// `0` and `chunk_shape + 1` layers. Neighbours that aren't set read as the `empty` voxel.
+///
+/// Each read adds up a few table entries to find the chunk and the voxel in it, so meshing is somewhat slower than on a
+/// padded array. In exchange, there's no copy and no padded array to keep around.
+pub struct ChunkNeighborhood<'a, T, S> {
+    chunk_shape: S,
+    chunks: [Option<&'a [T]>; 27],
+    empty: T,
+    // Linearizes padded points with power of 2 strides, so an index splits back into coordinates with shifts and masks.
+    index_shape: RuntimePow2Shape<u32, 3>,
+    // For each axis and padded coordinate, the parts of the chunk index and of the index within that chunk. Adding up
+    // the parts of all three axes finds a voxel without branching on which chunk it's in.
+    axis_offsets: [Vec<(u32, u32)>; 3],
+}
+
+impl<'a, T, S> ChunkNeighborhood<'a, T, S>
+where
+    S: Shape<3, Coord = u32>,
+{
+    /// Creates a neighborhood around the `center` chunk, without any neighbours.
+    pub fn new(chunk_shape: S, center: &'a [T], empty: T) -> Self {
+        assert_chunk_size(&chunk_shape, center);
+        let shape = chunk_shape.as_array();
+        assert!(
+            shape.iter().all(|&s| s > 0),
+            "chunk_shape={shape:?} has non-positive shape"
+        );
+        let bits = shape.map(|s| (s + 2).next_power_of_two().trailing_zeros());
+        assert!(
+            bits.iter().sum::<u32>() < u32::BITS,
+            "chunk_shape={shape:?} is too large to index"
+        );
+
+        let axis_offsets = [0, 1, 2].map(|axis| {
+            let mut unit = [0; 3];
+            unit[axis] = 1;
+            // Shapes linearize with a constant stride along each axis.
+            let local_stride = chunk_shape.linearize(unit);
+            // Same as the strides of `chunk_index`.
+            let chunk_stride = [1, 3, 9][axis];
+            (0..shape[axis] + 2)
+                .map(|p| {
+                    let (offset, local) = if p == 0 {
+                        (0, shape[axis] - 1)
+                    } else if p > shape[axis] {
+                        (2, 0)
+                    } else {
+                        (1, p - 1)
+                    };
+                    (offset * chunk_stride, local * local_stride)
+                })
+                .collect()
+        });
+
+        let mut chunks = [None; 27];
+        chunks[chunk_index([0; 3])] = Some(center);
+        Self {
+            chunk_shape,
+            chunks,
+            empty,
+            index_shape: RuntimePow2Shape::<u32, 3>::new(bits),
+            axis_offsets,
+        }
+    }
+
+    /// Sets the neighbouring chunk at `offset` (in units of chunks) from the centre chunk. Each component of `offset` must be
+    /// in `[-1, 1]`, and `offset` can't be the centre itself.
+    pub fn set_neighbor(&mut self, offset: [i32; 3], chunk: &'a [T]) {
+        assert!(
+            offset.iter().all(|&d| (-1..=1).contains(&d)) && offset != [0; 3],
+            "offset={offset:?} is not a neighbour of the centre chunk"
+        );
+        assert_chunk_size(&self.chunk_shape, chunk);
+        self.chunks[chunk_index(offset)] = Some(chunk);
+    }
+
+    /// The shape of the padded array that this neighborhood stands in for.
+    pub fn padded_shape(&self) -> [u32; 3] {
+        self.chunk_shape.as_array().map(|s| s + 2)
+    }
+
+    /// Returns the voxel at `p` in padded coordinates. `p` must be within [`Self::padded_shape`].
+    pub fn get(&self, p: [u32; 3]) -> &T {
+        let padded_shape = self.padded_shape();
+        assert!(
+            (0..3).all(|i| p[i] < padded_shape[i]),
+            "p={p:?} is out of bounds"
+        );
+        unsafe { self.get_unchecked(self.index_shape.linearize(p)) }
+    }
+
+    fn interior(&self) -> Extent<UVec3> {
+        Extent::from_min_and_shape(UVec3::ONE, UVec3::from(self.chunk_shape.as_array()))
+    }
+}
+
+impl<'a, T, S> VoxelAccess for ChunkNeighborhood<'a, T, S>
+where
+    S: Shape<3, Coord = u32>,
+{
+    type Voxel = T;
+    type Shape = RuntimePow2Shape<u32, 3>;
+
+    #[inline]
+    fn shape(&self) -> &Self::Shape {
+        &self.index_shape
+    }
+
+    #[inline]
+    unsafe fn get_unchecked(&self, index: u32) -> &T {
+        let p = self.index_shape.delinearize(index);
+        let (mut chunk, mut local) = (0, 0);
+        for (offsets, p) in self.axis_offsets.iter().zip(p) {
+            let (chunk_offset, local_offset) = *offsets.get_unchecked(p as usize);
+            chunk += chunk_offset;
+            local += local_offset;
+        }
+        match self.chunks.get_unchecked(chunk as usize) {
+            Some(chunk) => chunk.get_unchecked(local as usize),
+            None => &self.empty,
+        }
+    }
+}
+
+fn chunk_index([dx, dy, dz]: [i32; 3]) -> usize {
+    ((dx + 1) + 3 * (dy + 1) + 9 * (dz + 1)) as usize
+}
+
+fn assert_chunk_size<T, S>(chunk_shape: &S, chunk: &[T])
+where
+    S: Shape<3, Coord = u32>,
+{
+    assert!(
+        chunk_shape.size() as usize <= chunk.len(),
+        "chunk size {:?} is less than the shape size {:?}; would cause access out of bounds",
+        chunk.len(),
+        chunk_shape.size()
+    );
+}
+
+/// Same as [`greedy_quads`](crate::greedy_quads) over the padded array of `neighborhood`, meshing the whole centre chunk.
+///
+/// The output is identical to copying the neighborhood into a padded array and meshing `[0, chunk_shape + 1]`.
+pub fn greedy_quads_neighborhood<T, S>(
+    neighborhood: &ChunkNeighborhood<T, S>,
+    faces: &[OrientedBlockFace; 6],
+    output: &mut GreedyQuadsBuffer,
+) where
+    T: MergeVoxel,
+    S: Shape<3, Coord = u32>,
+{
+    greedy_quads_neighborhood_with_merge_strategy::<_, _, VoxelMerger<T>>(
+        neighborhood,
+        faces,
+        output,
+    )
+}
+
+/// Same as [`greedy_quads_neighborhood`], but with a custom quad merging strategy like
+/// [`greedy_quads_with_merge_strategy`](crate::greedy_quads_with_merge_strategy).
+pub fn greedy_quads_neighborhood_with_merge_strategy<T, S, Merger>(
+    neighborhood: &ChunkNeighborhood<T, S>,
+    faces: &[OrientedBlockFace; 6],
+    output: &mut GreedyQuadsBuffer,
+) where
+    T: Voxel,
+    S: Shape<3, Coord = u32>,
+    Merger: MergeStrategy<Voxel = T>,
+{
+    output.reset(neighborhood.shape().usize());
+    let GreedyQuadsBuffer {
+        visited,
+        ao,
+        quads: QuadBuffer { groups },
+        ..
+    } = output;
+
+    let interior = neighborhood.interior();
+
+    for ((group, ao_group), face) in groups.iter_mut().zip(ao.iter_mut()).zip(faces.iter()) {
+        greedy_quads_for_face::<_, Merger>(neighborhood, interior, face, visited, group, ao_group);
+    }
+}
+
+/// Same as [`visible_block_faces`](crate::visible_block_faces) over the padded array of `neighborhood`, meshing the whole
+/// centre chunk.
+///
+/// The output is identical to copying the neighborhood into a padded array and meshing `[0, chunk_shape + 1]`.
+pub fn visible_block_faces_neighborhood<T, S>(
+    neighborhood: &ChunkNeighborhood<T, S>,
+    faces: &[OrientedBlockFace; 6],
+    output: &mut UnitQuadBuffer,
+) where
+    T: Voxel,
+    S: Shape<3, Coord = u32>,
+{
+    visible_block_faces_for_extent(
+        neighborhood.shape(),
+        |index| IdentityVoxel::from(unsafe { neighborhood.get_unchecked(index) }),
+        neighborhood.interior(),
+        faces,
+        output,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{minimums, noise_voxels, NoiseVoxel, XorShift};
+    use crate::{
+        greedy_quads, greedy_quads_with_merge_strategy, visible_block_faces, AoVoxelMerger,
+        RIGHT_HANDED_Y_UP_CONFIG,
+    };
+    use ndshape::{ConstShape, ConstShape3u32};
+
+    type ChunkShape = ConstShape3u32<7, 5, 6>;
+    type PaddedShape = ConstShape3u32<9, 7, 8>;
+
+    const EMPTY: NoiseVoxel = NoiseVoxel(0);
+
+    fn noise_chunks() -> Vec<Vec<NoiseVoxel>> {
+        let mut rng = XorShift(0x9e37_79b9);
+        // Mostly full so that quads get merged.
+        let values = [0, 1, 2, 2, 2, 2, 2, 3];
+        (0..27)
+            .map(|_| noise_voxels(&mut rng, ChunkShape::SIZE as usize, &values))
+            .collect()
+    }
+
+    fn neighborhood<'a>(
+        chunks: &'a [Vec<NoiseVoxel>],
+        skip: &[[i32; 3]],
+    ) -> ChunkNeighborhood<'a, NoiseVoxel, ChunkShape> {
+        let mut neighborhood =
+            ChunkNeighborhood::new(ChunkShape {}, &chunks[chunk_index([0; 3])], EMPTY);
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let offset = [dx, dy, dz];
+                    if offset != [0; 3] && !skip.contains(&offset) {
+                        neighborhood.set_neighbor(offset, &chunks[chunk_index(offset)]);
+                    }
+                }
+            }
+        }
+        neighborhood
+    }
+
+    fn padded_copy(neighborhood: &ChunkNeighborhood<NoiseVoxel, ChunkShape>) -> Vec<NoiseVoxel> {
+        (0..PaddedShape::SIZE)
+            .map(|i| *neighborhood.get(PaddedShape {}.delinearize(i)))
+            .collect()
+    }
+
+    #[test]
+    fn get_reads_from_neighbours() {
+        let chunks = noise_chunks();
+        let neighborhood = neighborhood(&chunks, &[[1, 1, 1]]);
+
+        assert_eq!(*neighborhood.get([1, 1, 1]), chunks[chunk_index([0; 3])][0]);
+        let last = ChunkShape {}.linearize([6, 4, 5]) as usize;
+        assert_eq!(
+            *neighborhood.get([0, 0, 0]),
+            chunks[chunk_index([-1; 3])][last]
+        );
+        let index = ChunkShape {}.linearize([6, 2, 0]) as usize;
+        assert_eq!(
+            *neighborhood.get([0, 3, 7]),
+            chunks[chunk_index([-1, 0, 1])][index]
+        );
+        assert_eq!(*neighborhood.get([8, 6, 7]), EMPTY);
+    }
+
+    #[test]
+    fn matches_meshing_padded_copy() {
+        let chunks = noise_chunks();
+        let faces = &RIGHT_HANDED_Y_UP_CONFIG.faces;
+
+        for skip in [&[][..], &[[1, 0, 0], [0, -1, 0], [-1, 1, 1]][..]] {
+            let neighborhood = neighborhood(&chunks, skip);
+            let padded = padded_copy(&neighborhood);
+
+            let mut expected = GreedyQuadsBuffer::new(padded.len());
+            greedy_quads_with_merge_strategy::<_, _, AoVoxelMerger<NoiseVoxel>>(
+                &padded,
+                &PaddedShape {},
+                [0; 3],
+                [8, 6, 7],
+                faces,
+                &mut expected,
+            );
+            let mut actual = GreedyQuadsBuffer::new(0);
+            greedy_quads_neighborhood_with_merge_strategy::<_, _, AoVoxelMerger<NoiseVoxel>>(
+                &neighborhood,
+                faces,
+                &mut actual,
+            );
+            assert_eq!(actual.quads.groups, expected.quads.groups);
+            assert_eq!(actual.ao, expected.ao);
+
+            greedy_quads(
+                &padded,
+                &PaddedShape {},
+                [0; 3],
+                [8, 6, 7],
+                faces,
+                &mut expected,
+            );
+            greedy_quads_neighborhood(&neighborhood, faces, &mut actual);
+            assert_eq!(actual.quads.groups, expected.quads.groups);
+
+            let mut expected = UnitQuadBuffer::new();
+            visible_block_faces(
+                &padded,
+                &PaddedShape {},
+                [0; 3],
+                [8, 6, 7],
+                faces,
+                &mut expected,
+            );
+            let mut actual = UnitQuadBuffer::new();
+            visible_block_faces_neighborhood(&neighborhood, faces, &mut actual);
+            for (expected, actual) in expected.groups.iter().zip(actual.groups.iter()) {
+                assert_eq!(minimums(expected), minimums(actual));
+            }
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use crate::{GreedyQuadsBuffer, OrientedBlockFace, SignedAxis, UnorientedQuad};
+
+/// The largest coordinate that fits in a packed position.
+pub const PACKED_MAX_COORD: u32 = 63;
+
+/// The largest material that fits in a [`PackedQuad`].
+pub const PACKED_QUAD_MAX_MATERIAL: u32 = (1 << 21) - 1;
+
+/// The largest material that fits in a [`PackedVertex`].
+pub const PACKED_VERTEX_MAX_MATERIAL: u32 = (1 << 9) - 1;
+
+/// A whole quad packed into two `u32`s, for meshes that are expanded into vertices on the GPU (vertex pulling).
+///
+/// Coordinates are chunk-local, so they must be at most [`PACKED_MAX_COORD`]. This is enough for any extent that
+/// [`binary_greedy_quads`](crate::binary_greedy_quads) accepts.
+///
+/// The layout, from the least significant bit:
+///
+/// | word | bits   | field                                       |
+/// |------|--------|---------------------------------------------|
+/// | 0    | 0..6   | minimum X                                   |
+/// | 0    | 6..12  | minimum Y                                   |
+/// | 0    | 12..18 | minimum Z                                   |
+/// | 0    | 18..24 | width - 1                                   |
+/// | 0    | 24..30 | height - 1                                  |
+/// | 1    | 0..3   | face, as the discriminant of [`SignedAxis`] |
+/// | 1    | 3..11  | AO of the 4 corners, 2 bits each            |
+/// | 1    | 11..32 | material                                    |
+///
+/// The corners are in the order of [`OrientedBlockFace::quad_corners`]. So a shader decodes the width as
+/// `((word0 >> 18u) & 63u) + 1u`, and the AO of corner `i` as `(word1 >> (3u + 2u * i)) & 3u`. The `{N, U, V}` axes of each
+/// face have to be known by the shader, for example as a table indexed by the face.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct PackedQuad(pub [u32; 2]);
+
+impl PackedQuad {
+    /// Packs `quad` on `face`. `ao` must be in `[0, 3]`, as from [`vertex_ao`](crate::vertex_ao).
+    ///
+    /// # Panics
+    ///
+    /// If any of the values don't fit in their bits.
+    pub fn new(
+        face: &OrientedBlockFace,
+        quad: &UnorientedQuad,
+        ao: [u8; 4],
+        material: u32,
+    ) -> Self {
+        assert!(
+            quad.minimum.iter().all(|&c| c <= PACKED_MAX_COORD),
+            "quad minimum {:?} doesn't fit in a packed position",
+            quad.minimum
+        );
+        assert!(
+            (1..=PACKED_MAX_COORD + 1).contains(&quad.width)
+                && (1..=PACKED_MAX_COORD + 1).contains(&quad.height),
+            "quad size {}x{} doesn't fit in a packed quad",
+            quad.width,
+            quad.height
+        );
+        assert!(ao.iter().all(|&a| a <= 3), "ao={ao:?} must be in [0, 3]");
+        assert!(
+            material <= PACKED_QUAD_MAX_MATERIAL,
+            "material {material} doesn't fit in a packed quad"
+        );
+
+        let [x, y, z] = quad.minimum;
+        let word0 = x | y << 6 | z << 12 | (quad.width - 1) << 18 | (quad.height - 1) << 24;
+        let mut word1 = face.normal_axis() as u32 | material << 11;
+        for (i, a) in ao.into_iter().enumerate() {
+            word1 |= (a as u32) << (3 + 2 * i);
+        }
+        Self([word0, word1])
+    }
+
+    /// The minimum voxel of the quad.
+    #[inline]
+    pub fn minimum(&self) -> [u32; 3] {
+        let word0 = self.0[0];
+        [word0 & 63, (word0 >> 6) & 63, (word0 >> 12) & 63]
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        ((self.0[0] >> 18) & 63) + 1
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        ((self.0[0] >> 24) & 63) + 1
+    }
+
+    /// The quad without its face.
+    #[inline]
+    pub fn quad(&self) -> UnorientedQuad {
+        UnorientedQuad {
+            minimum: self.minimum(),
+            width: self.width(),
+            height: self.height(),
+        }
+    }
+
+    /// The normal of the face that the quad is on.
+    #[inline]
+    pub fn face(&self) -> SignedAxis {
+        SignedAxis::ALL[(self.0[1] & 7) as usize]
+    }
+
+    /// The AO of the 4 corners, in the order of [`OrientedBlockFace::quad_corners`].
+    #[inline]
+    pub fn ao(&self) -> [u8; 4] {
+        [0, 1, 2, 3].map(|i| ((self.0[1] >> (3 + 2 * i)) & 3) as u8)
+    }
+
+    #[inline]
+    pub fn material(&self) -> u32 {
+        self.0[1] >> 11
+    }
+}
+
+/// A single vertex packed into one `u32`.
+///
+/// Coordinates are chunk-local, so they must be at most [`PACKED_MAX_COORD`].
+///
+/// The layout, from the least significant bit:
+///
+/// | bits   | field                                       |
+/// |--------|---------------------------------------------|
+/// | 0..6   | X                                           |
+/// | 6..12  | Y                                           |
+/// | 12..18 | Z                                           |
+/// | 18..21 | face, as the discriminant of [`SignedAxis`] |
+/// | 21..23 | AO                                          |
+/// | 23..32 | material                                    |
+///
+/// So a shader decodes the position as `vec3(v & 63u, (v >> 6u) & 63u, (v >> 12u) & 63u)` and the material as `v >> 23u`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct PackedVertex(pub u32);
+
+impl PackedVertex {
+    /// Packs a vertex at `position` of a face with normal `face`. `ao` must be in `[0, 3]`.
+    ///
+    /// # Panics
+    ///
+    /// If any of the values don't fit in their bits.
+    pub fn new(position: [u32; 3], face: SignedAxis, ao: u8, material: u32) -> Self {
+        assert!(
+            position.iter().all(|&c| c <= PACKED_MAX_COORD),
+            "position {position:?} doesn't fit in a packed position"
+        );
+        assert!(ao <= 3, "ao={ao} must be in [0, 3]");
+        assert!(
+            material <= PACKED_VERTEX_MAX_MATERIAL,
+            "material {material} doesn't fit in a packed vertex"
+        );
+
+        let [x, y, z] = position;
+        Self(x | y << 6 | z << 12 | (face as u32) << 18 | (ao as u32) << 21 | material << 23)
+    }
+
+    /// Packs the 4 corners of `quad` on `face`, in the order of [`OrientedBlockFace::quad_corners`]. Use
+    /// [`OrientedBlockFace::quad_mesh_indices`] to make triangles.
+    pub fn quad_vertices(
+        face: &OrientedBlockFace,
+        quad: &UnorientedQuad,
+        ao: [u8; 4],
+        material: u32,
+    ) -> [Self; 4] {
+        let corners = face.quad_corners(quad);
+        let normal = face.normal_axis();
+        [0, 1, 2, 3].map(|i| Self::new(corners[i].to_array(), normal, ao[i], material))
+    }
+
+    #[inline]
+    pub fn position(&self) -> [u32; 3] {
+        [self.0 & 63, (self.0 >> 6) & 63, (self.0 >> 12) & 63]
+    }
+
+    /// The normal of the face that the vertex is on.
+    #[inline]
+    pub fn face(&self) -> SignedAxis {
+        SignedAxis::ALL[((self.0 >> 18) & 7) as usize]
+    }
+
+    #[inline]
+    pub fn ao(&self) -> u8 {
+        ((self.0 >> 21) & 3) as u8
+    }
+
+    #[inline]
+    pub fn material(&self) -> u32 {
+        self.0 >> 23
+    }
+}
+
+/// Packs all of the quads in `buffer`, appending them to `output` in order.
+///
+/// The AO comes from [`GreedyQuadsBuffer::ao`] when it was filled by the merge strategy, and is otherwise 3 (unoccluded).
+/// `material` is called with the group index and each quad to get its material.
+pub fn pack_greedy_quads(
+    faces: &[OrientedBlockFace; 6],
+    buffer: &GreedyQuadsBuffer,
+    mut material: impl FnMut(usize, &UnorientedQuad) -> u32,
+    output: &mut Vec<PackedQuad>,
+) {
+    output.reserve(buffer.quads.num_quads());
+    for (group_index, (group, face)) in buffer.quads.groups.iter().zip(faces.iter()).enumerate() {
+        let ao_group = &buffer.ao[group_index];
+        for (quad_index, quad) in group.iter().enumerate() {
+            let ao = ao_group.get(quad_index).copied().unwrap_or([3; 4]);
+            output.push(PackedQuad::new(face, quad, ao, material(group_index, quad)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{bool_voxels, BoolVoxel};
+    use crate::{greedy_quads_with_merge_strategy, AoVoxelMerger, RIGHT_HANDED_Y_UP_CONFIG};
+    use ndshape::ConstShape3u32;
+
+    #[test]
+    fn packed_quad_round_trips() {
+        for face in RIGHT_HANDED_Y_UP_CONFIG.faces.iter() {
+            let quad = UnorientedQuad {
+                minimum: [63, 0, 17],
+                width: 64,
+                height: 1,
+            };
+            let packed = PackedQuad::new(face, &quad, [0, 1, 2, 3], PACKED_QUAD_MAX_MATERIAL);
+
+            assert_eq!(packed.quad(), quad);
+            assert_eq!(packed.face(), face.normal_axis());
+            assert_eq!(packed.ao(), [0, 1, 2, 3]);
+            assert_eq!(packed.material(), PACKED_QUAD_MAX_MATERIAL);
+        }
+    }
+
+    #[test]
+    fn packed_vertex_round_trips() {
+        for (i, face) in SignedAxis::ALL.into_iter().enumerate() {
+            let position = [i as u32, 63, 40];
+            let packed = PackedVertex::new(position, face, 2, 300 + i as u32);
+
+            assert_eq!(packed.position(), position);
+            assert_eq!(packed.face(), face);
+            assert_eq!(packed.ao(), 2);
+            assert_eq!(packed.material(), 300 + i as u32);
+        }
+    }
+
+    #[test]
+    fn quad_vertices_match_corners() {
+        let face = &RIGHT_HANDED_Y_UP_CONFIG.faces[4];
+        let quad = UnorientedQuad {
+            minimum: [1, 2, 3],
+            width: 4,
+            height: 5,
+        };
+        let vertices = PackedVertex::quad_vertices(face, &quad, [3, 2, 1, 0], 7);
+
+        for ((vertex, corner), ao) in vertices
+            .iter()
+            .zip(face.quad_corners(&quad))
+            .zip([3, 2, 1, 0])
+        {
+            assert_eq!(vertex.position(), corner.to_array());
+            assert_eq!(vertex.face(), SignedAxis::PosY);
+            assert_eq!(vertex.ao(), ao);
+            assert_eq!(vertex.material(), 7);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn panics_with_position_out_of_range() {
+        PackedVertex::new([64, 0, 0], SignedAxis::PosX, 0, 0);
+    }
+
+    #[test]
+    fn packs_greedy_quads_with_ao() {
+        type SampleShape = ConstShape3u32<5, 5, 5>;
+        let voxels = bool_voxels(&SampleShape {}, |[x, y, z]| {
+            y == 1 && (1..4).contains(&x) && (1..4).contains(&z) || [x, y, z] == [2, 2, 2]
+        });
+        let faces = &RIGHT_HANDED_Y_UP_CONFIG.faces;
+        let mut buffer = GreedyQuadsBuffer::new(voxels.len());
+        greedy_quads_with_merge_strategy::<_, _, AoVoxelMerger<BoolVoxel>>(
+            &voxels,
+            &SampleShape {},
+            [0; 3],
+            [4; 3],
+            faces,
+            &mut buffer,
+        );
+
+        let mut packed = Vec::new();
+        pack_greedy_quads(
+            faces,
+            &buffer,
+            |group_index, _| group_index as u32,
+            &mut packed,
+        );
+
+        let mut packed = packed.into_iter();
+        for (group_index, group) in buffer.quads.groups.iter().enumerate() {
+            for (quad, ao) in group.iter().zip(buffer.ao[group_index].iter()) {
+                let p = packed.next().unwrap();
+                assert_eq!(p.quad(), *quad);
+                assert_eq!(p.face(), faces[group_index].normal_axis());
+                assert_eq!(p.ao(), *ao);
+                assert_eq!(p.material(), group_index as u32);
+            }
+        }
+        assert!(packed.next().is_none());
+    }
+}
@@ -0,0 +1,377 @@
+use crate::{bounds::assert_in_bounds, Axis, SignedAxis, Voxel, VoxelVisibility};
+
+use ilattice::glam::{IVec3, Vec3};
+use ndshape::Shape;
+
+/// The first voxel hit by a ray, as returned by [`raycast`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RaycastHit {
+    /// The voxel that was hit.
+    pub voxel: [u32; 3],
+    /// The face of `voxel` that the ray entered through. For block picking, the voxel to place a new block in is
+    /// `voxel + face.get_unit_vector()`.
+    pub face: SignedAxis,
+    /// The distance along the ray to the point where it entered `voxel`.
+    pub distance: f32,
+}
+
+/// Finds the first voxel in the extent `[min, max]` of `voxels` that is hit by a ray, using the same
+/// [`Voxel::get_visibility`] as the meshers: every voxel that isn't [`VoxelVisibility::Empty`] is hit.
+///
+/// Positions are in the space of the array, where the voxel at `p` covers the unit cube from `p` to `p + 1`, the same as
+/// the quads from the meshers. The ray starts at `origin` and goes at most `max_distance` in `direction`, which doesn't
+/// need to be normalized. A ray that starts outside of the extent is first moved to where it enters the extent.
+///
+/// If the ray starts inside of a voxel that is hit, that voxel is returned with a distance of 0, and `face` is the face
+/// facing most directly against `direction`.
+///
+/// ```
+/// # use block_mesh::ilattice::glam::Vec3;
+/// # use block_mesh::ndshape::{ConstShape, ConstShape3u32};
+/// # use block_mesh::{raycast, SignedAxis, VoxVoxel};
+/// type ChunkShape = ConstShape3u32<8, 8, 8>;
+///
+/// let mut voxels = [VoxVoxel::EMPTY; ChunkShape::SIZE as usize];
+/// voxels[ChunkShape::linearize([5, 2, 2]) as usize] = VoxVoxel(1);
+///
+/// let hit = raycast(
+///     &voxels,
+///     &ChunkShape {},
+///     [0; 3],
+///     [7; 3],
+///     Vec3::new(0.5, 2.5, 2.5),
+///     Vec3::X,
+///     100.0,
+/// )
+/// .unwrap();
+/// assert_eq!(hit.voxel, [5, 2, 2]);
+/// assert_eq!(hit.face, SignedAxis::NegX);
+/// assert_eq!(hit.distance, 4.5);
+/// ```
+pub fn raycast<T, S>(
+    voxels: &[T],
+    voxels_shape: &S,
+    min: [u32; 3],
+    max: [u32; 3],
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<RaycastHit>
+where
+    T: Voxel,
+    S: Shape<3, Coord = u32>,
+{
+    raycast_with_filter(
+        voxels,
+        voxels_shape,
+        min,
+        max,
+        origin,
+        direction,
+        max_distance,
+        |_| true,
+    )
+}
+
+/// Same as [`raycast`], but only voxels for which `filter` returns true can be hit. The ray passes through all others, as
+/// if they were empty.
+///
+/// For example, to pick blocks through glass and water, skip [`VoxelVisibility::Translucent`] voxels with
+/// `|voxel| voxel.get_visibility() == VoxelVisibility::Opaque`.
+#[allow(clippy::too_many_arguments)]
+pub fn raycast_with_filter<T, S>(
+    voxels: &[T],
+    voxels_shape: &S,
+    min: [u32; 3],
+    max: [u32; 3],
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    mut filter: impl FnMut(&T) -> bool,
+) -> Option<RaycastHit>
+where
+    T: Voxel,
+    S: Shape<3, Coord = u32>,
+{
+    assert_in_bounds(voxels, voxels_shape, min, max);
+
+    let direction = direction.try_normalize()?;
+    let min_i = IVec3::from(min.map(|c| c as i32));
+    let max_i = IVec3::from(max.map(|c| c as i32));
+
+    // Clip the ray to the box covered by the extent, keeping the axis it enters through.
+    let box_min = min_i.as_vec3();
+    let box_max = (max_i + IVec3::ONE).as_vec3();
+    let mut t_enter = 0.0f32;
+    let mut t_exit = max_distance;
+    let mut enter_axis = None;
+    for i in 0..3 {
+        if direction[i] == 0.0 {
+            if origin[i] < box_min[i] || origin[i] >= box_max[i] {
+                return None;
+            }
+            continue;
+        }
+        let t0 = (box_min[i] - origin[i]) / direction[i];
+        let t1 = (box_max[i] - origin[i]) / direction[i];
+        let (near, far) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+        if near > t_enter {
+            t_enter = near;
+            enter_axis = Some(i);
+        }
+        t_exit = t_exit.min(far);
+    }
+    if t_enter > t_exit {
+        return None;
+    }
+
+    let step = IVec3::from([0, 1, 2].map(|i| match direction[i] {
+        d if d > 0.0 => 1,
+        d if d < 0.0 => -1,
+        _ => 0,
+    }));
+    let against = |axis: usize| SignedAxis::new(-step[axis], AXES[axis]);
+
+    // The voxel containing the entry point. Rounding can put it just outside of the extent.
+    let start = origin + t_enter * direction;
+    let mut p = start.floor().as_ivec3().max(min_i).min(max_i);
+    if let Some(axis) = enter_axis {
+        p[axis] = if step[axis] > 0 {
+            min_i[axis]
+        } else {
+            max_i[axis]
+        };
+    }
+    let mut face = match enter_axis {
+        Some(axis) => against(axis),
+        None => against(min_axis(-direction.abs())),
+    };
+    let mut distance = t_enter;
+
+    // The distance along the ray to the next voxel boundary on each axis, and between boundaries.
+    let t_delta = direction.abs().recip();
+    let mut t_max = Vec3::from([0, 1, 2].map(|i| match step[i] {
+        0 => f32::INFINITY,
+        s => {
+            let boundary = if s > 0 { p[i] + 1 } else { p[i] };
+            (boundary as f32 - origin[i]) / direction[i]
+        }
+    }));
+
+    loop {
+        let voxel = p.as_uvec3().to_array();
+        let value = &voxels[voxels_shape.linearize(voxel) as usize];
+        if value.get_visibility() != VoxelVisibility::Empty && filter(value) {
+            return Some(RaycastHit {
+                voxel,
+                face,
+                distance,
+            });
+        }
+
+        let axis = min_axis(t_max);
+        distance = t_max[axis];
+        if distance > t_exit {
+            return None;
+        }
+        p[axis] += step[axis];
+        if p[axis] < min_i[axis] || p[axis] > max_i[axis] {
+            return None;
+        }
+        face = against(axis);
+        t_max[axis] += t_delta[axis];
+    }
+}
+
+const AXES: [Axis; 3] = [Axis::X, Axis::Y, Axis::Z];
+
+/// The index of the smallest component of `v`.
+fn min_axis(v: Vec3) -> usize {
+    (0..3).min_by(|&a, &b| v[a].total_cmp(&v[b])).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ndshape::{ConstShape, ConstShape3u32};
+
+    type SampleShape = ConstShape3u32<8, 8, 8>;
+
+    #[derive(Clone, Copy, Eq, PartialEq)]
+    enum Block {
+        Air,
+        Glass,
+        Stone,
+    }
+
+    impl Voxel for Block {
+        fn get_visibility(&self) -> VoxelVisibility {
+            match self {
+                Block::Air => VoxelVisibility::Empty,
+                Block::Glass => VoxelVisibility::Translucent,
+                Block::Stone => VoxelVisibility::Opaque,
+            }
+        }
+    }
+
+    fn cast(samples: &[Block], origin: Vec3, direction: Vec3) -> Option<RaycastHit> {
+        raycast(
+            samples,
+            &SampleShape {},
+            [0; 3],
+            [7; 3],
+            origin,
+            direction,
+            100.0,
+        )
+    }
+
+    #[test]
+    fn hits_each_face() {
+        let mut samples = [Block::Air; SampleShape::SIZE as usize];
+        samples[SampleShape {}.linearize([3, 4, 5]) as usize] = Block::Stone;
+        let center = Vec3::new(3.5, 4.5, 5.5);
+
+        for face in SignedAxis::ALL {
+            let normal = face.get_unit_vector().as_vec3();
+            // Off center, so the ray crosses boundaries on every axis.
+            let origin = center + 2.5 * normal + Vec3::new(0.2, -0.1, 0.3);
+            let hit = cast(&samples, origin, center - origin).unwrap();
+            assert_eq!(hit.voxel, [3, 4, 5]);
+            assert_eq!(hit.face, face);
+
+            let entry = origin + hit.distance * (center - origin).normalize();
+            let plane = center + 0.5 * normal;
+            let i = face.unsigned_axis().index();
+            assert!((entry[i] - plane[i]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut samples = [Block::Air; SampleShape::SIZE as usize];
+        for i in (0..samples.len()).step_by(23) {
+            samples[i] = Block::Stone;
+        }
+        let directions = [
+            Vec3::new(1.0, 0.3, 0.7),
+            Vec3::new(-0.2, 1.0, 0.5),
+            Vec3::new(0.4, -0.6, -1.0),
+            Vec3::new(-1.0, -1.0, 0.0),
+        ];
+        let mut num_hits = 0;
+        for i in 0..20 {
+            let origin = Vec3::new(
+                0.1 + (i as f32 * 0.37) % 7.8,
+                0.1 + (i as f32 * 0.73) % 7.8,
+                0.1 + (i as f32 * 1.31) % 7.8,
+            );
+            for &direction in directions.iter() {
+                let hit = cast(&samples, origin, direction);
+
+                // March in tiny steps to find the first solid voxel.
+                let d = direction.normalize();
+                let mut expected = None;
+                for k in 0..20000 {
+                    let p = (origin + (k as f32 * 0.001) * d).floor();
+                    if p.cmplt(Vec3::ZERO).any() || p.cmpge(Vec3::splat(8.0)).any() {
+                        break;
+                    }
+                    let voxel = p.as_uvec3().to_array();
+                    if samples[SampleShape {}.linearize(voxel) as usize] != Block::Air {
+                        expected = Some((voxel, k as f32 * 0.001));
+                        break;
+                    }
+                }
+
+                match (hit, expected) {
+                    (None, None) => {}
+                    (Some(hit), Some((voxel, distance))) => {
+                        assert_eq!(hit.voxel, voxel);
+                        assert!((hit.distance - distance).abs() < 0.002);
+                        num_hits += 1;
+                    }
+                    _ => panic!("origin={origin:?} direction={direction:?} hit={hit:?}"),
+                }
+            }
+        }
+        assert!(num_hits >= 20);
+    }
+
+    #[test]
+    fn enters_extent_from_outside() {
+        let mut samples = [Block::Air; SampleShape::SIZE as usize];
+        samples[SampleShape {}.linearize([2, 2, 2]) as usize] = Block::Stone;
+        samples[SampleShape {}.linearize([4, 2, 2]) as usize] = Block::Stone;
+
+        // The extent starts at x = 3, so the first block is skipped.
+        let hit = raycast(
+            &samples,
+            &SampleShape {},
+            [3, 0, 0],
+            [7; 3],
+            Vec3::new(-10.0, 2.5, 2.5),
+            Vec3::X,
+            100.0,
+        )
+        .unwrap();
+        assert_eq!(hit.voxel, [4, 2, 2]);
+        assert_eq!(hit.face, SignedAxis::NegX);
+        assert_eq!(hit.distance, 14.0);
+
+        // Out of reach.
+        assert_eq!(
+            raycast(
+                &samples,
+                &SampleShape {},
+                [0; 3],
+                [7; 3],
+                Vec3::new(-10.0, 2.5, 2.5),
+                Vec3::X,
+                11.0,
+            ),
+            None
+        );
+        // Misses the extent.
+        assert_eq!(cast(&samples, Vec3::new(-1.0, 9.0, 2.5), Vec3::X), None);
+        // Starts inside of a block.
+        let hit = cast(
+            &samples,
+            Vec3::new(2.5, 2.5, 2.5),
+            Vec3::new(0.1, -1.0, 0.0),
+        )
+        .unwrap();
+        assert_eq!(hit.voxel, [2, 2, 2]);
+        assert_eq!(hit.face, SignedAxis::PosY);
+        assert_eq!(hit.distance, 0.0);
+        // No direction.
+        assert_eq!(cast(&samples, Vec3::ZERO, Vec3::ZERO), None);
+    }
+
+    #[test]
+    fn filter_skips_translucent() {
+        let mut samples = [Block::Air; SampleShape::SIZE as usize];
+        samples[SampleShape {}.linearize([2, 1, 1]) as usize] = Block::Glass;
+        samples[SampleShape {}.linearize([5, 1, 1]) as usize] = Block::Stone;
+        let origin = Vec3::new(0.5, 1.5, 1.5);
+
+        let hit = cast(&samples, origin, Vec3::X).unwrap();
+        assert_eq!(hit.voxel, [2, 1, 1]);
+
+        let hit = raycast_with_filter(
+            &samples,
+            &SampleShape {},
+            [0; 3],
+            [7; 3],
+            origin,
+            Vec3::X,
+            100.0,
+            |voxel| voxel.get_visibility() == VoxelVisibility::Opaque,
+        )
+        .unwrap();
+        assert_eq!(hit.voxel, [5, 1, 1]);
+        assert_eq!(hit.face, SignedAxis::NegX);
+        assert_eq!(hit.distance, 4.5);
+    }
+}
@@ -1,12 +1,12 @@
 use crate::{
-    bounds::assert_in_bounds, IdentityVoxel, OrientedBlockFace, UnitQuadBuffer, UnorientedUnitQuad, Voxel, VoxelVisibility,
+    bounds::assert_in_bounds, face_is_visible, IdentityVoxel, OrientedBlockFace, UnitQuadBuffer,
+    UnorientedUnitQuad, Voxel, VoxelVisibility,
 };
 
 use ilattice::glam::UVec3;
 use ilattice::prelude::Extent;
 use ndshape::Shape;
 
-
 /// A fast and simple meshing algorithm that produces a single quad for every visible face of a block.
 ///
 /// This is faster than [`greedy_quads`](crate::greedy_quads) but it produces many more quads.
@@ -55,13 +55,35 @@ pub fn visible_block_faces_with_voxel_view<'a, T, V, S>(
     let interior =
         Extent::from_min_and_shape(interior.minimum.as_uvec3(), interior.shape.as_uvec3());
 
+    visible_block_faces_for_extent(
+        voxels_shape,
+        |index| V::from(unsafe { voxels.get_unchecked(index as usize) }),
+        interior,
+        faces,
+        output,
+    );
+}
+
+/// Meshes the faces of every voxel in `interior`, where `get` returns the voxel at an index linearized by
+/// `voxels_shape`. `interior` must be at least one voxel away from the bounds of the voxels.
+pub(crate) fn visible_block_faces_for_extent<V, S>(
+    voxels_shape: &S,
+    get: impl Fn(u32) -> V,
+    interior: Extent<UVec3>,
+    faces: &[OrientedBlockFace; 6],
+    output: &mut UnitQuadBuffer,
+) where
+    V: Voxel,
+    S: Shape<3, Coord = u32>,
+{
     let kernel_strides =
         faces.map(|face| voxels_shape.linearize(face.signed_normal().as_uvec3().to_array()));
+    let normals = faces.map(|face| face.normal_axis());
 
     for p in interior.iter3() {
         let p_array = p.to_array();
         let p_index = voxels_shape.linearize(p_array);
-        let p_voxel = V::from(unsafe { voxels.get_unchecked(p_index as usize) });
+        let p_voxel = get(p_index);
 
         if let VoxelVisibility::Empty = p_voxel.get_visibility() {
             continue;
@@ -69,17 +91,9 @@ pub fn visible_block_faces_with_voxel_view<'a, T, V, S>(
 
         for (face_index, face_stride) in kernel_strides.into_iter().enumerate() {
             let neighbor_index = p_index.wrapping_add(face_stride);
-            let neighbor_voxel = V::from(unsafe { voxels.get_unchecked(neighbor_index as usize) });
-
-            // TODO: If the face lies between two transparent voxels, we choose not to mesh it. We might need to extend the
-            // IsOpaque trait with different levels of transparency to support this.
-            let face_needs_mesh = match neighbor_voxel.get_visibility() {
-                VoxelVisibility::Empty => true,
-                VoxelVisibility::Translucent => p_voxel.get_visibility() == VoxelVisibility::Opaque,
-                VoxelVisibility::Opaque => false,
-            };
+            let neighbor_voxel = get(neighbor_index);
 
-            if face_needs_mesh {
+            if face_is_visible(&p_voxel, &neighbor_voxel, normals[face_index]) {
                 output.groups[face_index].push(UnorientedUnitQuad { minimum: p_array });
             }
         }
@@ -89,13 +103,14 @@ pub fn visible_block_faces_with_voxel_view<'a, T, V, S>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::{minimums, BoolVoxel, Material};
     use crate::RIGHT_HANDED_Y_UP_CONFIG;
     use ndshape::{ConstShape, ConstShape3u32};
 
     #[test]
     #[should_panic]
     fn panics_with_max_out_of_bounds_access() {
-        let samples = [EMPTY; SampleShape::SIZE as usize];
+        let samples = [BoolVoxel::EMPTY; SampleShape::SIZE as usize];
         let mut buffer = UnitQuadBuffer::new();
         visible_block_faces(
             &samples,
@@ -110,7 +125,7 @@ mod tests {
     #[test]
     #[should_panic]
     fn panics_with_min_out_of_bounds_access() {
-        let samples = [EMPTY; SampleShape::SIZE as usize];
+        let samples = [BoolVoxel::EMPTY; SampleShape::SIZE as usize];
         let mut buffer = UnitQuadBuffer::new();
         visible_block_faces(
             &samples,
@@ -122,22 +137,58 @@ mod tests {
         );
     }
 
-    type SampleShape = ConstShape3u32<34, 34, 34>;
+    #[test]
+    fn meshes_faces_between_translucent_groups() {
+        let samples = glass_and_water_samples();
+        let mut buffer = UnitQuadBuffer::new();
+        visible_block_faces(
+            &samples,
+            &RowShape {},
+            [0; 3],
+            [4, 2, 2],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
 
-    /// Basic voxel type with one byte of texture layers
-    #[derive(Default, Clone, Copy, Eq, PartialEq)]
-    struct BoolVoxel(bool);
+        // -X faces.
+        assert_eq!(minimums(&buffer.groups[0]), [[1, 1, 1], [2, 1, 1]]);
+        // +X faces.
+        assert_eq!(minimums(&buffer.groups[3]), [[1, 1, 1], [3, 1, 1]]);
+    }
 
-    const EMPTY: BoolVoxel = BoolVoxel(false);
+    #[test]
+    fn uses_face_visibility() {
+        // A slab only occludes its neighbour below it, so the stone's +X face is visible.
+        let mut samples = [Material::Air; RowShape::SIZE as usize];
+        samples[RowShape {}.linearize([1, 1, 1]) as usize] = Material::Stone;
+        samples[RowShape {}.linearize([2, 1, 1]) as usize] = Material::Slab;
 
-    impl Voxel for BoolVoxel {
-        fn get_visibility(&self) -> VoxelVisibility {
-            if *self == EMPTY {
-                VoxelVisibility::Empty
-            } else {
-                VoxelVisibility::Opaque
-            }
-        }
+        let mut buffer = UnitQuadBuffer::new();
+        visible_block_faces(
+            &samples,
+            &RowShape {},
+            [0; 3],
+            [4, 2, 2],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+
+        // -X faces.
+        assert_eq!(minimums(&buffer.groups[0]), [[1, 1, 1]]);
+        // +X faces.
+        assert_eq!(minimums(&buffer.groups[3]), [[1, 1, 1], [2, 1, 1]]);
     }
 
+    type SampleShape = ConstShape3u32<34, 34, 34>;
+
+    type RowShape = ConstShape3u32<5, 3, 3>;
+
+    /// A glass voxel next to two water voxels along the X axis.
+    fn glass_and_water_samples() -> [Material; RowShape::SIZE as usize] {
+        let mut samples = [Material::Air; RowShape::SIZE as usize];
+        samples[RowShape {}.linearize([1, 1, 1]) as usize] = Material::Glass;
+        samples[RowShape {}.linearize([2, 1, 1]) as usize] = Material::Water;
+        samples[RowShape {}.linearize([3, 1, 1]) as usize] = Material::Water;
+        samples
+    }
 }
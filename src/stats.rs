@@ -0,0 +1,158 @@
+use crate::{OrientedBlockFace, QuadGroups};
+
+use ilattice::glam::UVec3;
+use ilattice::prelude::Extent;
+use std::collections::BTreeMap;
+
+/// Statistics about the quads from any of the meshers, for tracking mesh budgets.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshStats {
+    /// The number of quads in each face group.
+    pub quads_per_group: [usize; 6],
+    /// The total area of all quads, in voxel faces.
+    pub surface_area: u64,
+    /// The number of quads of each `[width, height]`.
+    pub quad_sizes: BTreeMap<[u32; 2], usize>,
+    /// The smallest extent containing every voxel that has a face in a quad, or `None` if there are no quads.
+    pub bounds: Option<Extent<UVec3>>,
+}
+
+impl MeshStats {
+    /// Computes the statistics of `quads`, which were generated with `faces`.
+    pub fn new(faces: &[OrientedBlockFace; 6], quads: &impl QuadGroups) -> Self {
+        let mut stats = Self::default();
+        quads.for_each_quad(|group_index, quad| {
+            let face = &faces[group_index];
+            stats.quads_per_group[group_index] += 1;
+            stats.surface_area += quad.width as u64 * quad.height as u64;
+            *stats
+                .quad_sizes
+                .entry([quad.width, quad.height])
+                .or_default() += 1;
+
+            let shape = UVec3::ONE + face.u * (quad.width - 1) + face.v * (quad.height - 1);
+            let extent = Extent::from_min_and_shape(UVec3::from(quad.minimum), shape);
+            stats.bounds = Some(match stats.bounds {
+                Some(bounds) => bounds.bound_union(&extent),
+                None => extent,
+            });
+        });
+        stats
+    }
+
+    /// Returns the total number of quads.
+    pub fn num_quads(&self) -> usize {
+        self.quads_per_group.iter().sum()
+    }
+
+    /// Returns the number of triangles in each face group, with 2 per quad.
+    pub fn triangles_per_group(&self) -> [usize; 6] {
+        self.quads_per_group.map(|n| 2 * n)
+    }
+
+    /// Returns the total number of triangles, with 2 per quad.
+    pub fn num_triangles(&self) -> usize {
+        2 * self.num_quads()
+    }
+
+    /// Returns how many quads [`visible_block_faces`](crate::visible_block_faces) would generate for the same faces, one
+    /// per voxel face.
+    pub fn num_unit_quads(&self) -> u64 {
+        self.surface_area
+    }
+
+    /// Returns the number of quads from [`visible_block_faces`](crate::visible_block_faces) for every quad here. This is 1
+    /// for the output of `visible_block_faces` itself, and higher the more the greedy meshers merged. Returns 1 if there are
+    /// no quads.
+    pub fn compression_ratio(&self) -> f64 {
+        match self.num_quads() {
+            0 => 1.0,
+            n => self.surface_area as f64 / n as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        greedy_quads, visible_block_faces, GreedyQuadsBuffer, UnitQuadBuffer, UnorientedUnitQuad,
+        VoxVoxel, RIGHT_HANDED_Y_UP_CONFIG,
+    };
+
+    use ndshape::{ConstShape, ConstShape3u32};
+
+    #[test]
+    fn unit_cube_stats() {
+        let mut quads = UnitQuadBuffer::new();
+        for group in quads.groups.iter_mut() {
+            group.push(UnorientedUnitQuad { minimum: [2, 3, 4] });
+        }
+        let stats = MeshStats::new(&RIGHT_HANDED_Y_UP_CONFIG.faces, &quads);
+
+        assert_eq!(stats.quads_per_group, [1; 6]);
+        assert_eq!(stats.triangles_per_group(), [2; 6]);
+        assert_eq!(stats.num_triangles(), 12);
+        assert_eq!(stats.surface_area, 6);
+        assert_eq!(stats.quad_sizes, BTreeMap::from([([1, 1], 6)]));
+        assert_eq!(
+            stats.bounds,
+            Some(Extent::from_min_and_shape(UVec3::new(2, 3, 4), UVec3::ONE))
+        );
+        assert_eq!(stats.compression_ratio(), 1.0);
+    }
+
+    #[test]
+    fn greedy_stats_match_visible_block_faces() {
+        type SampleShape = ConstShape3u32<6, 5, 4>;
+
+        // A 4x3x2 box.
+        let mut samples = [VoxVoxel::EMPTY; SampleShape::SIZE as usize];
+        for i in 0..SampleShape::SIZE {
+            let [x, y, z] = SampleShape::delinearize(i);
+            if (1..5).contains(&x) && (1..4).contains(&y) && (1..3).contains(&z) {
+                samples[i as usize] = VoxVoxel(1);
+            }
+        }
+        let faces = &RIGHT_HANDED_Y_UP_CONFIG.faces;
+        let mut buffer = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads(
+            &samples,
+            &SampleShape {},
+            [0; 3],
+            [5, 4, 3],
+            faces,
+            &mut buffer,
+        );
+        let mut unit_quads = UnitQuadBuffer::new();
+        visible_block_faces(
+            &samples,
+            &SampleShape {},
+            [0; 3],
+            [5, 4, 3],
+            faces,
+            &mut unit_quads,
+        );
+
+        let stats = MeshStats::new(faces, &buffer.quads);
+        let unit_stats = MeshStats::new(faces, &unit_quads);
+
+        assert_eq!(stats.num_quads(), 6);
+        assert_eq!(stats.num_unit_quads(), unit_quads.num_quads() as u64);
+        assert_eq!(stats.surface_area, 2 * (4 * 3 + 4 * 2 + 3 * 2));
+        assert_eq!(stats.compression_ratio(), 52.0 / 6.0);
+        assert_eq!(stats.quad_sizes.values().sum::<usize>(), 6);
+        assert_eq!(
+            stats.bounds,
+            Some(Extent::from_min_and_shape(UVec3::ONE, UVec3::new(4, 3, 2)))
+        );
+        assert_eq!(stats.bounds, unit_stats.bounds);
+    }
+
+    #[test]
+    fn empty_stats() {
+        let stats = MeshStats::new(&RIGHT_HANDED_Y_UP_CONFIG.faces, &UnitQuadBuffer::new());
+        assert_eq!(stats, MeshStats::default());
+        assert_eq!(stats.compression_ratio(), 1.0);
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{MeshBuffer, MeshBuilder, QuadGroups};
+
+use ilattice::glam::UVec3;
+use std::collections::HashMap;
+
+/// Same as [`MeshBuilder::build_into`], but without T-junctions, so the mesh has no cracks when rasterized.
+///
+/// Quads of different sizes meet at T-junctions, where the corner of one quad lies on the edge of another. Rounding makes the
+/// rasterized edges miss each other by a fraction of a pixel, which shows as sparkling cracks. This finds every quad corner
+/// that lies on the edge of another quad, whether the two quads are coplanar or meet at an angle, and in any face group. Each
+/// quad with such corners on its edges becomes a polygon with the extra vertices on its boundary, which is triangulated as
+/// a fan around a new vertex at the quad's center. Quads without extra vertices are triangulated as usual.
+///
+/// Only the corners of `quads` are considered, so T-junctions with the quads of neighbouring chunks remain.
+///
+/// ```
+/// # use block_mesh::{build_without_t_junctions, MeshBuffer, MeshBuilder, QuadBuffer, RIGHT_HANDED_Y_UP_CONFIG};
+/// # let quads = QuadBuffer::new();
+/// let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG);
+/// let mut mesh = MeshBuffer::new();
+/// build_without_t_junctions(&builder, &quads, &mut mesh);
+/// ```
+pub fn build_without_t_junctions(
+    builder: &MeshBuilder,
+    quads: &impl QuadGroups,
+    output: &mut MeshBuffer,
+) {
+    output.reset();
+    let faces = &builder.config.faces;
+
+    // Every axis-aligned line through a quad corner, with the sorted coordinates of all corners on it.
+    let mut lines = HashMap::<LineKey, Vec<u32>>::new();
+    quads.for_each_quad(|group_index, quad| {
+        for corner in faces[group_index].quad_corners(&quad) {
+            for axis in 0..3 {
+                lines
+                    .entry(LineKey::new(corner, axis))
+                    .or_default()
+                    .push(corner[axis]);
+            }
+        }
+    });
+    for coords in lines.values_mut() {
+        coords.sort_unstable();
+        coords.dedup();
+    }
+
+    let mut boundary = Vec::new();
+    quads.for_each_quad(|group_index, quad| {
+        let face = &faces[group_index];
+        let corners = face.quad_corners(&quad);
+        let tex_coords = face.tex_coords(builder.config.u_flip_face, builder.flip_v, &quad);
+        let normal = face.signed_normal().as_vec3().to_array();
+        let start = output.positions.len() as u32;
+
+        // Walk the boundary in the order 0, 1, 3, 2 of `quad_corners`, adding the corners of other quads along each edge.
+        boundary.clear();
+        for (from, to) in [(0, 1), (1, 3), (3, 2), (2, 0)] {
+            boundary.push(corners[from]);
+            push_points_on_edge(&lines, corners[from], corners[to], &mut boundary);
+        }
+
+        if boundary.len() == 4 {
+            output
+                .indices
+                .extend_from_slice(&face.quad_mesh_indices(start));
+            output
+                .positions
+                .extend_from_slice(&face.quad_mesh_positions(&quad, builder.voxel_size));
+            output.normals.extend_from_slice(&[normal; 4]);
+            output.tex_coords.extend_from_slice(&tex_coords);
+            return;
+        }
+
+        // Texture coordinates are affine in the position on the quad.
+        let [t0, t1, t2, _] = tex_coords;
+        let tex_coord =
+            |s: f32, t: f32| [0, 1].map(|i| t0[i] + s * (t1[i] - t0[i]) + t * (t2[i] - t0[i]));
+        let (width, height) = (quad.width as f32, quad.height as f32);
+        for &p in boundary.iter() {
+            let d = p - corners[0];
+            output
+                .positions
+                .push((builder.voxel_size * p.as_vec3()).to_array());
+            output.tex_coords.push(tex_coord(
+                d.dot(face.u) as f32 / width,
+                d.dot(face.v) as f32 / height,
+            ));
+        }
+        let center = (corners[0] + corners[3]).as_vec3() / 2.0;
+        output
+            .positions
+            .push((builder.voxel_size * center).to_array());
+        output.tex_coords.push(tex_coord(0.5, 0.5));
+        output
+            .normals
+            .extend(std::iter::repeat_n(normal, boundary.len() + 1));
+
+        // The boundary is counterclockwise around the U x V direction, so it's reversed for faces where that points inward.
+        let counter_clockwise = face.n_sign() * face.permutation().sign() > 0;
+        let center_index = start + boundary.len() as u32;
+        let n = boundary.len() as u32;
+        for i in 0..n {
+            let (a, b) = (start + i, start + (i + 1) % n);
+            if counter_clockwise {
+                output.indices.extend_from_slice(&[center_index, a, b]);
+            } else {
+                output.indices.extend_from_slice(&[center_index, b, a]);
+            }
+        }
+    });
+}
+
+/// Identifies an axis-aligned lattice line by its axis and the coordinates of the other two axes.
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+struct LineKey {
+    axis: usize,
+    others: [u32; 2],
+}
+
+impl LineKey {
+    fn new(p: UVec3, axis: usize) -> Self {
+        let others = [(axis + 1) % 3, (axis + 2) % 3].map(|i| p[i]);
+        Self { axis, others }
+    }
+}
+
+/// Pushes the points of `lines` strictly between `from` and `to`, in order from `from`. The points must differ on exactly
+/// one axis.
+fn push_points_on_edge(
+    lines: &HashMap<LineKey, Vec<u32>>,
+    from: UVec3,
+    to: UVec3,
+    output: &mut Vec<UVec3>,
+) {
+    let axis = (0..3).find(|&i| from[i] != to[i]).unwrap();
+    let coords = &lines[&LineKey::new(from, axis)];
+    let (lo, hi) = (from[axis].min(to[axis]), from[axis].max(to[axis]));
+    let between =
+        &coords[coords.partition_point(|&c| c <= lo)..coords.partition_point(|&c| c < hi)];
+    let point = |c: u32| {
+        let mut p = from;
+        p[axis] = c;
+        p
+    };
+    if from[axis] < to[axis] {
+        output.extend(between.iter().map(|&c| point(c)));
+    } else {
+        output.extend(between.iter().rev().map(|&c| point(c)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        greedy_quads, GreedyQuadsBuffer, UnitQuadBuffer, UnorientedUnitQuad, VoxVoxel,
+        RIGHT_HANDED_Y_UP_CONFIG,
+    };
+
+    use ilattice::glam::Vec3;
+    use ndshape::{ConstShape, ConstShape3u32};
+
+    type SampleShape = ConstShape3u32<6, 6, 6>;
+
+    /// A 4x1x4 floor with a single block on top of one corner and a 2x1x1 bar on top of another, so the greedy quads of the
+    /// floor's top have T-junctions with each other and with the sides of the blocks.
+    fn greedy_steps() -> GreedyQuadsBuffer {
+        let mut samples = [VoxVoxel::EMPTY; SampleShape::SIZE as usize];
+        for i in 0..SampleShape::SIZE {
+            let [x, y, z] = SampleShape::delinearize(i);
+            let interior = (1..5).contains(&x) && (1..5).contains(&z);
+            if (y == 1 && interior)
+                || (y == 2 && [x, z] == [1, 1])
+                || (y == 2 && z == 4 && (3..5).contains(&x))
+            {
+                samples[i as usize] = VoxVoxel(1);
+            }
+        }
+        let mut buffer = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads(
+            &samples,
+            &SampleShape {},
+            [0; 3],
+            [5; 3],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+        buffer
+    }
+
+    fn triangles(mesh: &MeshBuffer) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+        mesh.indices
+            .chunks_exact(3)
+            .map(|t| [0, 1, 2].map(|i| Vec3::from(mesh.positions[t[i] as usize])))
+    }
+
+    #[test]
+    fn removes_t_junctions() {
+        let buffer = greedy_steps();
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG).voxel_size(0.5);
+        let plain = builder.build(&buffer.quads);
+        let mut mesh = MeshBuffer::new();
+        build_without_t_junctions(&builder, &buffer.quads, &mut mesh);
+
+        // No vertex lies strictly inside the edge of a triangle.
+        let on_edge = |mesh: &MeshBuffer| {
+            triangles(mesh).any(|triangle| {
+                (0..3).any(|i| {
+                    let (a, b) = (triangle[i], triangle[(i + 1) % 3]);
+                    mesh.positions.iter().map(|&p| Vec3::from(p)).any(|p| {
+                        let (ap, ab) = (p - a, b - a);
+                        ap.cross(ab).length_squared() == 0.0
+                            && ap.dot(ab) > 0.0
+                            && ap.dot(ab) < ab.length_squared()
+                    })
+                })
+            })
+        };
+        assert!(on_edge(&plain));
+        assert!(!on_edge(&mesh));
+
+        // The triangles cover the same area, facing the same way.
+        let area = |mesh: &MeshBuffer| -> f32 {
+            triangles(mesh)
+                .map(|[a, b, c]| (b - a).cross(c - a).length() / 2.0)
+                .sum()
+        };
+        assert_eq!(area(&mesh), area(&plain));
+        for (triangle, t) in triangles(&mesh).zip(mesh.indices.chunks_exact(3)) {
+            let [a, b, c] = triangle;
+            let normal = Vec3::from(mesh.normals[t[0] as usize]);
+            assert!((b - a).cross(c - a).dot(normal) > 0.0);
+        }
+        assert_eq!(mesh.normals.len(), mesh.positions.len());
+        assert_eq!(mesh.tex_coords.len(), mesh.positions.len());
+    }
+
+    #[test]
+    fn keeps_quads_without_t_junctions() {
+        let mut quads = UnitQuadBuffer::new();
+        for group in quads.groups.iter_mut() {
+            group.push(UnorientedUnitQuad { minimum: [0; 3] });
+        }
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG);
+        let mut mesh = MeshBuffer::new();
+        build_without_t_junctions(&builder, &quads, &mut mesh);
+
+        assert_eq!(mesh, builder.build(&quads));
+    }
+
+    #[test]
+    fn interpolates_tex_coords() {
+        let buffer = greedy_steps();
+        let builder = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG);
+        let mut mesh = MeshBuffer::new();
+        build_without_t_junctions(&builder, &buffer.quads, &mut mesh);
+
+        // Texture coordinates repeat once per voxel, so they differ by the same amount as positions along each edge.
+        for t in mesh.indices.chunks_exact(3) {
+            for (i, j) in [(0, 1), (1, 2), (2, 0)] {
+                let (a, b) = (t[i] as usize, t[j] as usize);
+                let dp = (Vec3::from(mesh.positions[b]) - Vec3::from(mesh.positions[a])).abs();
+                let [du, dv] =
+                    [0, 1].map(|k| (mesh.tex_coords[b][k] - mesh.tex_coords[a][k]).abs());
+                assert_eq!(dp.x + dp.y + dp.z, du + dv);
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Voxel types and pseudo-random fixtures shared by the unit tests.
+
+use crate::{MergeVoxel, SignedAxis, UnorientedQuad, Voxel, VoxelVisibility};
+
+use ndshape::Shape;
+
+/// A xorshift32 generator, so that noise fixtures are the same on every run.
+pub(crate) struct XorShift(pub u32);
+
+impl XorShift {
+    pub fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    /// Picks one of `choices` uniformly.
+    pub fn choose<T: Copy>(&mut self, choices: &[T]) -> T {
+        choices[self.next() as usize % choices.len()]
+    }
+}
+
+/// A voxel that is empty for 0, translucent for 1, and opaque otherwise. Faces only merge with the same value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct NoiseVoxel(pub u8);
+
+impl Voxel for NoiseVoxel {
+    fn get_visibility(&self) -> VoxelVisibility {
+        match self.0 {
+            0 => VoxelVisibility::Empty,
+            1 => VoxelVisibility::Translucent,
+            _ => VoxelVisibility::Opaque,
+        }
+    }
+}
+
+impl MergeVoxel for NoiseVoxel {
+    type MergeValue = u8;
+    type MergeValueFacingNeighbour = u8;
+
+    fn merge_value(&self) -> Self::MergeValue {
+        self.0
+    }
+
+    fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour {
+        self.0
+    }
+}
+
+/// `len` voxels with values picked from `values`.
+pub(crate) fn noise_voxels(rng: &mut XorShift, len: usize, values: &[u8]) -> Vec<NoiseVoxel> {
+    (0..len).map(|_| NoiseVoxel(rng.choose(values))).collect()
+}
+
+/// A voxel that is either empty or opaque. All opaque faces merge.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct BoolVoxel(pub bool);
+
+impl BoolVoxel {
+    pub const EMPTY: Self = Self(false);
+}
+
+impl Voxel for BoolVoxel {
+    fn get_visibility(&self) -> VoxelVisibility {
+        if self.0 {
+            VoxelVisibility::Opaque
+        } else {
+            VoxelVisibility::Empty
+        }
+    }
+}
+
+impl MergeVoxel for BoolVoxel {
+    type MergeValue = bool;
+    type MergeValueFacingNeighbour = bool;
+
+    fn merge_value(&self) -> Self::MergeValue {
+        self.0
+    }
+
+    fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour {
+        self.0
+    }
+}
+
+/// Voxels for every point of `shape`, filled where `filled` returns true.
+pub(crate) fn bool_voxels<S>(shape: &S, filled: impl Fn([u32; 3]) -> bool) -> Vec<BoolVoxel>
+where
+    S: Shape<3, Coord = u32>,
+{
+    (0..shape.size())
+        .map(|i| BoolVoxel(filled(shape.delinearize(i))))
+        .collect()
+}
+
+/// Voxels with a mix of visibilities, translucency groups, and face-specific behaviour.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Material {
+    Air,
+    Glass,
+    Water,
+    Stone,
+    /// Only occludes the voxel below it.
+    Slab,
+    /// Has dirt sides, so only its top face differs from dirt.
+    Grass,
+    Dirt,
+}
+
+impl Voxel for Material {
+    fn get_visibility(&self) -> VoxelVisibility {
+        match self {
+            Material::Air => VoxelVisibility::Empty,
+            Material::Glass | Material::Water => VoxelVisibility::Translucent,
+            Material::Stone | Material::Slab | Material::Grass | Material::Dirt => {
+                VoxelVisibility::Opaque
+            }
+        }
+    }
+
+    fn get_face_visibility(&self, face: SignedAxis) -> VoxelVisibility {
+        match (self, face) {
+            (Material::Slab, SignedAxis::NegY) => VoxelVisibility::Opaque,
+            (Material::Slab, _) => VoxelVisibility::Translucent,
+            _ => self.get_visibility(),
+        }
+    }
+
+    fn get_translucency_group(&self) -> u32 {
+        *self as u32
+    }
+}
+
+impl MergeVoxel for Material {
+    type MergeValue = Self;
+    type MergeValueFacingNeighbour = Self;
+
+    fn merge_value(&self) -> Self::MergeValue {
+        *self
+    }
+
+    fn merge_value_for_face(&self, face: SignedAxis) -> Self::MergeValue {
+        match (self, face) {
+            (Material::Grass, SignedAxis::PosY) => Material::Grass,
+            (Material::Grass, _) => Material::Dirt,
+            _ => *self,
+        }
+    }
+
+    fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour {
+        *self
+    }
+}
+
+/// The minimum voxel of each quad in `group`.
+pub(crate) fn minimums<Q>(group: &[Q]) -> Vec<[u32; 3]>
+where
+    Q: Copy + Into<UnorientedQuad>,
+{
+    group.iter().map(|&q| q.into().minimum).collect()
+}
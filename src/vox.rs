@@ -0,0 +1,380 @@
+use crate::{MergeVoxel, Voxel, VoxelVisibility};
+
+use ndshape::{RuntimeShape, Shape};
+use std::fmt;
+use std::io::{self, Read};
+
+/// A voxel loaded from a [`VoxFile`], holding its palette index. Index 0 is empty.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct VoxVoxel(pub u8);
+
+impl VoxVoxel {
+    pub const EMPTY: Self = Self(0);
+}
+
+impl Voxel for VoxVoxel {
+    fn get_visibility(&self) -> VoxelVisibility {
+        if *self == Self::EMPTY {
+            VoxelVisibility::Empty
+        } else {
+            VoxelVisibility::Opaque
+        }
+    }
+}
+
+impl MergeVoxel for VoxVoxel {
+    type MergeValue = u8;
+    type MergeValueFacingNeighbour = u8;
+
+    fn merge_value(&self) -> Self::MergeValue {
+        self.0
+    }
+
+    fn merge_value_facing_neighbour(&self) -> Self::MergeValueFacingNeighbour {
+        self.0
+    }
+}
+
+/// A single model in a [`VoxFile`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VoxModel {
+    /// The dimensions of the model. MagicaVoxel uses +Z as the up axis.
+    pub size: [u32; 3],
+    /// The `[x, y, z]` position and palette index of each non-empty voxel.
+    pub voxels: Vec<([u8; 3], u8)>,
+}
+
+impl VoxModel {
+    /// Copies the model into a new array with 1 voxel of empty padding on every side, ready to be meshed.
+    ///
+    /// The model's voxel at `p` ends up at `p + 1` in the array, so the whole model can be meshed with `min = [0; 3]`
+    /// and `max = shape.as_array().map(|d| d - 1)`.
+    ///
+    /// ```
+    /// # use block_mesh::{greedy_quads, GreedyQuadsBuffer, VoxModel, RIGHT_HANDED_Y_UP_CONFIG};
+    /// # use block_mesh::ndshape::Shape;
+    /// # let model = VoxModel { size: [2, 2, 2], voxels: vec![([0, 1, 0], 5)] };
+    /// let (shape, voxels) = model.padded_voxels();
+    /// let mut buffer = GreedyQuadsBuffer::new(voxels.len());
+    /// greedy_quads(
+    ///     &voxels,
+    ///     &shape,
+    ///     [0; 3],
+    ///     shape.as_array().map(|d| d - 1),
+    ///     &RIGHT_HANDED_Y_UP_CONFIG.faces,
+    ///     &mut buffer,
+    /// );
+    /// assert_eq!(buffer.quads.num_quads(), 6);
+    /// ```
+    pub fn padded_voxels(&self) -> (RuntimeShape<u32, 3>, Vec<VoxVoxel>) {
+        let shape = RuntimeShape::<u32, 3>::new(self.size.map(|d| d + 2));
+        let mut voxels = vec![VoxVoxel::EMPTY; shape.size() as usize];
+        for &(p, index) in self.voxels.iter() {
+            voxels[shape.linearize(p.map(|c| c as u32 + 1)) as usize] = VoxVoxel(index);
+        }
+        (shape, voxels)
+    }
+}
+
+/// The contents of a MagicaVoxel `.vox` file, as read by [`read_vox`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VoxFile {
+    /// The models in the order they appear in the file. Scene graph transforms are not applied.
+    pub models: Vec<VoxModel>,
+    /// The RGBA color of each palette index. Index 0 is unused, since it means empty.
+    pub palette: [[u8; 4]; 256],
+}
+
+/// An error from [`read_vox`].
+#[derive(Debug)]
+pub enum VoxError {
+    Io(io::Error),
+    /// The file doesn't start with `VOX `.
+    InvalidMagic,
+    /// The file has no `MAIN` chunk.
+    MissingMainChunk,
+    /// A chunk is truncated or has the wrong size for its ID, or a `SIZE` chunk has a dimension that is 0 or more than
+    /// 256.
+    InvalidChunk([u8; 4]),
+    /// An `XYZI` chunk has no `SIZE` chunk before it.
+    MissingSize,
+    /// A voxel lies outside of its model's `SIZE`.
+    VoxelOutOfBounds([u8; 3]),
+}
+
+impl fmt::Display for VoxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read .vox file: {e}"),
+            Self::InvalidMagic => write!(f, "not a .vox file"),
+            Self::MissingMainChunk => write!(f, "missing MAIN chunk"),
+            Self::InvalidChunk(id) => {
+                write!(f, "invalid {} chunk", String::from_utf8_lossy(id))
+            }
+            Self::MissingSize => write!(f, "XYZI chunk without a preceding SIZE chunk"),
+            Self::VoxelOutOfBounds(p) => write!(f, "voxel {p:?} is outside of its model"),
+        }
+    }
+}
+
+impl std::error::Error for VoxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for VoxError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Reads a MagicaVoxel `.vox` file.
+///
+/// The `SIZE` and `XYZI` chunks of every model are read, as well as the `RGBA` palette. If the file has no palette, the
+/// MagicaVoxel default palette is used. All other chunks are skipped.
+pub fn read_vox(reader: &mut impl Read) -> Result<VoxFile, VoxError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    if bytes.len() < 8 || &bytes[..4] != b"VOX " {
+        return Err(VoxError::InvalidMagic);
+    }
+    let (main, _) = read_chunk(&bytes[8..]).ok_or(VoxError::MissingMainChunk)?;
+    if main.id != *b"MAIN" {
+        return Err(VoxError::MissingMainChunk);
+    }
+
+    let mut file = VoxFile {
+        models: Vec::new(),
+        palette: default_palette(),
+    };
+    let mut size = None;
+    let mut rest = main.children;
+    while !rest.is_empty() {
+        let (Chunk { id, content, .. }, next) =
+            read_chunk(rest).ok_or(VoxError::InvalidChunk(*b"MAIN"))?;
+        rest = next;
+        match &id {
+            b"SIZE" => {
+                if content.len() < 12 {
+                    return Err(VoxError::InvalidChunk(id));
+                }
+                let dims = [0, 1, 2].map(|i| read_u32(&content[4 * i..]));
+                // Voxel coordinates are bytes, so no model can be larger than this.
+                if dims.iter().any(|&d| d == 0 || d > 256) {
+                    return Err(VoxError::InvalidChunk(id));
+                }
+                size = Some(dims);
+            }
+            b"XYZI" => {
+                let size = size.take().ok_or(VoxError::MissingSize)?;
+                let num_voxels = content
+                    .get(..4)
+                    .map(read_u32)
+                    .ok_or(VoxError::InvalidChunk(id))? as usize;
+                let voxel_bytes = content[4..]
+                    .get(..num_voxels.saturating_mul(4))
+                    .ok_or(VoxError::InvalidChunk(id))?;
+                let mut voxels = Vec::with_capacity(num_voxels);
+                for v in voxel_bytes.chunks_exact(4) {
+                    let p = [v[0], v[1], v[2]];
+                    if p.iter().zip(size.iter()).any(|(&c, &d)| c as u32 >= d) {
+                        return Err(VoxError::VoxelOutOfBounds(p));
+                    }
+                    voxels.push((p, v[3]));
+                }
+                file.models.push(VoxModel { size, voxels });
+            }
+            b"RGBA" => {
+                if content.len() < 1024 {
+                    return Err(VoxError::InvalidChunk(id));
+                }
+                // The chunk holds the colors of indices 1 through 255, followed by an unused entry.
+                for (i, c) in content[..1020].chunks_exact(4).enumerate() {
+                    file.palette[i + 1] = [c[0], c[1], c[2], c[3]];
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(file)
+}
+
+/// A chunk of a `.vox` file.
+struct Chunk<'a> {
+    id: [u8; 4],
+    content: &'a [u8],
+    children: &'a [u8],
+}
+
+/// Reads the chunk at the start of `bytes`, also returning the bytes after it.
+fn read_chunk(bytes: &[u8]) -> Option<(Chunk<'_>, &[u8])> {
+    let header = bytes.get(..12)?;
+    let id = [header[0], header[1], header[2], header[3]];
+    let content_size = read_u32(&header[4..]) as usize;
+    let children_size = read_u32(&header[8..]) as usize;
+    let rest = &bytes[12..];
+    let content = rest.get(..content_size)?;
+    let children = rest[content_size..].get(..children_size)?;
+    let chunk = Chunk {
+        id,
+        content,
+        children,
+    };
+    Some((chunk, &rest[content_size + children_size..]))
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// The palette MagicaVoxel uses for files without an `RGBA` chunk.
+fn default_palette() -> [[u8; 4]; 256] {
+    let mut palette = [[0; 4]; 256];
+    let mut i = 1;
+    // A 6x6x6 color cube, without black.
+    for r in (0..6).rev() {
+        for g in (0..6).rev() {
+            for b in (0..6).rev() {
+                if r + g + b > 0 {
+                    palette[i] = [r * 0x33, g * 0x33, b * 0x33, 0xff];
+                    i += 1;
+                }
+            }
+        }
+    }
+    // Ramps of red, green, blue, and gray, skipping the shades that are already in the cube.
+    let ramp = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+    for channels in [
+        [true, false, false],
+        [false, true, false],
+        [false, false, true],
+        [true; 3],
+    ] {
+        for shade in ramp {
+            let [r, g, b] = channels.map(|on| if on { shade } else { 0 });
+            palette[i] = [r, g, b, 0xff];
+            i += 1;
+        }
+    }
+    palette
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{greedy_quads, GreedyQuadsBuffer, RIGHT_HANDED_Y_UP_CONFIG};
+
+    fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend((content.len() as u32).to_le_bytes());
+        bytes.extend((children.len() as u32).to_le_bytes());
+        bytes.extend(content);
+        bytes.extend(children);
+        bytes
+    }
+
+    fn model_chunks(size: [u32; 3], voxels: &[[u8; 4]]) -> Vec<u8> {
+        let size: Vec<u8> = size.iter().flat_map(|d| d.to_le_bytes()).collect();
+        let mut xyzi = (voxels.len() as u32).to_le_bytes().to_vec();
+        xyzi.extend(voxels.iter().flatten());
+        let mut bytes = chunk(b"SIZE", &size, &[]);
+        bytes.extend(chunk(b"XYZI", &xyzi, &[]));
+        bytes
+    }
+
+    fn vox_file(children: &[u8]) -> Vec<u8> {
+        let mut bytes = b"VOX ".to_vec();
+        bytes.extend(150u32.to_le_bytes());
+        bytes.extend(chunk(b"MAIN", &[], children));
+        bytes
+    }
+
+    #[test]
+    fn reads_models_and_palette() {
+        let mut children = chunk(b"PACK", &2u32.to_le_bytes(), &[]);
+        children.extend(model_chunks([2, 3, 4], &[[0, 0, 0, 1], [1, 2, 3, 200]]));
+        children.extend(model_chunks([1, 1, 1], &[[0, 0, 0, 7]]));
+        let rgba: Vec<u8> = (0..256u32).flat_map(|i| [i as u8, 0, 0, 255]).collect();
+        children.extend(chunk(b"RGBA", &rgba, &[]));
+        children.extend(chunk(b"nTRN", &[0; 16], &[]));
+
+        let file = read_vox(&mut vox_file(&children).as_slice()).unwrap();
+
+        assert_eq!(
+            file.models,
+            [
+                VoxModel {
+                    size: [2, 3, 4],
+                    voxels: vec![([0, 0, 0], 1), ([1, 2, 3], 200)]
+                },
+                VoxModel {
+                    size: [1, 1, 1],
+                    voxels: vec![([0, 0, 0], 7)]
+                }
+            ]
+        );
+        assert_eq!(file.palette[1], [0, 0, 0, 255]);
+        assert_eq!(file.palette[200], [199, 0, 0, 255]);
+    }
+
+    #[test]
+    fn uses_default_palette() {
+        let file = read_vox(&mut vox_file(&model_chunks([1, 1, 1], &[])).as_slice()).unwrap();
+
+        assert_eq!(file.palette[1], [0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(file.palette[2], [0xff, 0xff, 0xcc, 0xff]);
+        assert_eq!(file.palette[215], [0, 0, 0x33, 0xff]);
+        assert_eq!(file.palette[216], [0xee, 0, 0, 0xff]);
+        assert_eq!(file.palette[255], [0x11, 0x11, 0x11, 0xff]);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(matches!(
+            read_vox(&mut &b"PNG "[..]),
+            Err(VoxError::InvalidMagic)
+        ));
+        assert!(matches!(
+            read_vox(&mut vox_file(&model_chunks([1, 1, 1], &[[1, 0, 0, 1]])).as_slice()),
+            Err(VoxError::VoxelOutOfBounds([1, 0, 0]))
+        ));
+        for size in [[0, 1, 1], [1, 257, 1], [1, 1, u32::MAX]] {
+            assert!(matches!(
+                read_vox(&mut vox_file(&model_chunks(size, &[])).as_slice()),
+                Err(VoxError::InvalidChunk(id)) if id == *b"SIZE"
+            ));
+        }
+        assert!(read_vox(&mut vox_file(&model_chunks([256; 3], &[])).as_slice()).is_ok());
+        let mut truncated = vox_file(&model_chunks([1, 1, 1], &[[0, 0, 0, 1]]));
+        truncated.pop();
+        assert!(read_vox(&mut truncated.as_slice()).is_err());
+    }
+
+    #[test]
+    fn meshes_padded_voxels() {
+        let model = VoxModel {
+            size: [3, 1, 1],
+            voxels: vec![([0, 0, 0], 1), ([1, 0, 0], 1), ([2, 0, 0], 2)],
+        };
+        let (shape, voxels) = model.padded_voxels();
+        assert_eq!(shape.as_array(), [5, 3, 3]);
+        assert_eq!(voxels[shape.linearize([3, 1, 1]) as usize], VoxVoxel(2));
+
+        let mut buffer = GreedyQuadsBuffer::new(voxels.len());
+        greedy_quads(
+            &voxels,
+            &shape,
+            [0; 3],
+            [4, 2, 2],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut buffer,
+        );
+        // The two palette indices can't merge, except on the X faces which each only touch one of them.
+        assert_eq!(buffer.quads.num_quads(), 2 + 4 * 2);
+    }
+}
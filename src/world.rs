@@ -0,0 +1,242 @@
+use crate::{
+    greedy_quads, visible_block_faces, GreedyQuadsBuffer, MergeVoxel, MeshBuffer,
+    OrientedBlockFace, UnitQuadBuffer, Voxel,
+};
+
+use ilattice::glam::{DVec3, IVec3, Vec3};
+use ndshape::Shape;
+
+/// Same as [`visible_block_faces`], but with the extent given in world space, for a voxel array whose `[0, 0, 0]` voxel is
+/// at `origin` in the world.
+///
+/// The quads are still in the space of the array. [`OrientedBlockFace::quad_corners_in_world`], [`world_translation`], and
+/// [`relative_translation`] place them in the world.
+///
+/// # Panics
+///
+/// If `min` or `max` is outside of the array.
+pub fn visible_block_faces_in_world<T, S>(
+    voxels: &[T],
+    voxels_shape: &S,
+    origin: IVec3,
+    min: IVec3,
+    max: IVec3,
+    faces: &[OrientedBlockFace; 6],
+    output: &mut UnitQuadBuffer,
+) where
+    T: Voxel,
+    S: Shape<3, Coord = u32>,
+{
+    visible_block_faces(
+        voxels,
+        voxels_shape,
+        world_to_local(origin, min),
+        world_to_local(origin, max),
+        faces,
+        output,
+    )
+}
+
+/// Same as [`greedy_quads`], but with the extent given in world space, for a voxel array whose `[0, 0, 0]` voxel is at
+/// `origin` in the world.
+///
+/// For a chunk with 1 voxel of padding whose first voxel is at `chunk_min`, `origin` is `chunk_min - 1`. Chunks at negative
+/// coordinates work the same as any other:
+///
+/// ```
+/// # use block_mesh::ilattice::glam::IVec3;
+/// # use block_mesh::ndshape::{ConstShape, ConstShape3u32};
+/// # use block_mesh::{greedy_quads_in_world, GreedyQuadsBuffer, VoxVoxel, RIGHT_HANDED_Y_UP_CONFIG};
+/// type ChunkShape = ConstShape3u32<18, 18, 18>;
+///
+/// let chunk_min = IVec3::new(-16, 0, -32);
+/// let origin = chunk_min - IVec3::ONE;
+/// let mut voxels = [VoxVoxel::EMPTY; ChunkShape::SIZE as usize];
+/// voxels[ChunkShape::linearize([1, 1, 1]) as usize] = VoxVoxel(1);
+///
+/// let faces = &RIGHT_HANDED_Y_UP_CONFIG.faces;
+/// let mut buffer = GreedyQuadsBuffer::new(voxels.len());
+/// greedy_quads_in_world(
+///     &voxels,
+///     &ChunkShape {},
+///     origin,
+///     origin,
+///     origin + IVec3::splat(17),
+///     faces,
+///     &mut buffer,
+/// );
+///
+/// // The +Y face of the voxel at `chunk_min`.
+/// let quad = &buffer.quads.groups[4][0];
+/// let corners = faces[4].quad_corners_in_world(quad, origin);
+/// assert_eq!(corners[0], IVec3::new(-16, 1, -32));
+/// ```
+///
+/// # Panics
+///
+/// If `min` or `max` is outside of the array.
+pub fn greedy_quads_in_world<T, S>(
+    voxels: &[T],
+    voxels_shape: &S,
+    origin: IVec3,
+    min: IVec3,
+    max: IVec3,
+    faces: &[OrientedBlockFace; 6],
+    output: &mut GreedyQuadsBuffer,
+) where
+    T: MergeVoxel,
+    S: Shape<3, Coord = u32>,
+{
+    greedy_quads(
+        voxels,
+        voxels_shape,
+        world_to_local(origin, min),
+        world_to_local(origin, max),
+        faces,
+        output,
+    )
+}
+
+/// The world-space position of the array-space point `[0, 0, 0]` for a voxel array whose `[0, 0, 0]` voxel is at `origin`.
+///
+/// Meshes from [`MeshBuilder`](crate::MeshBuilder) have positions relative to their array, which keeps them small enough
+/// for `f32`. Using this as the translation of each mesh places it in the world, with enough precision for worlds far past
+/// the range where `f32` positions would wobble.
+pub fn world_translation(origin: IVec3, voxel_size: f64) -> DVec3 {
+    voxel_size * origin.as_dvec3()
+}
+
+/// Same as [`world_translation`], but relative to the voxel at `reference` instead of the world origin.
+///
+/// This is for floating origin rendering, where `reference` is a voxel near the camera. The difference is computed in
+/// integers, so it's exact even when both coordinates are far from the world origin.
+pub fn relative_translation(origin: IVec3, reference: IVec3, voxel_size: f32) -> Vec3 {
+    let [x, y, z] = [0, 1, 2].map(|i| (i64::from(origin[i]) - i64::from(reference[i])) as f32);
+    voxel_size * Vec3::new(x, y, z)
+}
+
+/// Returns the positions of `mesh` in world space, for a mesh built from an array whose `[0, 0, 0]` voxel is at `origin`.
+///
+/// `voxel_size` must be the voxel size that `mesh` was built with.
+pub fn world_positions(mesh: &MeshBuffer, origin: IVec3, voxel_size: f64) -> Vec<[f64; 3]> {
+    let translation = world_translation(origin, voxel_size);
+    mesh.positions
+        .iter()
+        .map(|&p| (translation + Vec3::from(p).as_dvec3()).to_array())
+        .collect()
+}
+
+/// Converts world-space `p` to the space of an array whose `[0, 0, 0]` voxel is at `origin`.
+fn world_to_local(origin: IVec3, p: IVec3) -> [u32; 3] {
+    [0, 1, 2].map(|i| {
+        u32::try_from(i64::from(p[i]) - i64::from(origin[i])).unwrap_or_else(|_| {
+            panic!("world position {p:?} is outside of the array at origin={origin:?}")
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MeshBuilder, VoxVoxel, RIGHT_HANDED_Y_UP_CONFIG};
+
+    use ndshape::{ConstShape, ConstShape3u32};
+
+    type SampleShape = ConstShape3u32<4, 4, 4>;
+
+    fn single_voxel() -> [VoxVoxel; SampleShape::SIZE as usize] {
+        let mut samples = [VoxVoxel::EMPTY; SampleShape::SIZE as usize];
+        samples[SampleShape {}.linearize([1, 1, 1]) as usize] = VoxVoxel(1);
+        samples
+    }
+
+    #[test]
+    fn world_extent_matches_local_extent() {
+        let samples = single_voxel();
+        let faces = &RIGHT_HANDED_Y_UP_CONFIG.faces;
+        let origin = IVec3::new(-40, 7, i32::MIN);
+
+        let mut local = UnitQuadBuffer::new();
+        visible_block_faces(&samples, &SampleShape {}, [0; 3], [3; 3], faces, &mut local);
+        let mut world = UnitQuadBuffer::new();
+        visible_block_faces_in_world(
+            &samples,
+            &SampleShape {},
+            origin,
+            origin,
+            origin + IVec3::splat(3),
+            faces,
+            &mut world,
+        );
+        assert_eq!(world.groups, local.groups);
+
+        let mut greedy = GreedyQuadsBuffer::new(samples.len());
+        greedy_quads_in_world(
+            &samples,
+            &SampleShape {},
+            origin,
+            origin,
+            origin + IVec3::splat(3),
+            faces,
+            &mut greedy,
+        );
+        for (face, group) in faces.iter().zip(greedy.quads.groups.iter()) {
+            for quad in group {
+                let local_corners = face.quad_corners(quad);
+                let world_corners = face.quad_corners_in_world(quad, origin);
+                for (l, w) in local_corners.iter().zip(world_corners) {
+                    assert_eq!(w - origin, l.as_ivec3());
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn world_extent_outside_array() {
+        let samples = single_voxel();
+        let mut output = UnitQuadBuffer::new();
+        visible_block_faces_in_world(
+            &samples,
+            &SampleShape {},
+            IVec3::ZERO,
+            -IVec3::ONE,
+            IVec3::splat(3),
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            &mut output,
+        );
+    }
+
+    #[test]
+    fn translations_far_from_world_origin() {
+        // f32 can't tell these apart, but their difference is exact.
+        let origin = IVec3::new(100_000_001, -100_000_001, 3);
+        let reference = IVec3::new(100_000_000, -100_000_000, 0);
+        assert_eq!(
+            relative_translation(origin, reference, 0.5),
+            Vec3::new(0.5, -0.5, 1.5)
+        );
+        assert_eq!(
+            world_translation(origin, 0.5),
+            DVec3::new(50_000_000.5, -50_000_000.5, 1.5)
+        );
+
+        let samples = single_voxel();
+        let mut quads = UnitQuadBuffer::new();
+        let faces = &RIGHT_HANDED_Y_UP_CONFIG.faces;
+        visible_block_faces(&samples, &SampleShape {}, [0; 3], [3; 3], faces, &mut quads);
+        let mesh = MeshBuilder::new(&RIGHT_HANDED_Y_UP_CONFIG)
+            .voxel_size(0.5)
+            .build(&quads);
+        let positions = world_positions(&mesh, origin, 0.5);
+
+        // The voxel at [1, 1, 1] spans [1, 2] in array space.
+        let min = DVec3::splat(0.5) + world_translation(origin, 0.5);
+        let max = min + DVec3::splat(0.5);
+        for p in positions.iter().map(|&p| DVec3::from(p)) {
+            assert!(p.cmpge(min).all() && p.cmple(max).all());
+        }
+        assert!(positions.contains(&min.to_array()));
+        assert!(positions.contains(&max.to_array()));
+    }
+}